@@ -0,0 +1,52 @@
+//! Hashing a dense panel over a large synthetic VCF, buffering all matches
+//! (`from_file` + `commit_sparse`) against committing in windows
+//! (`commit_stream`).
+//!
+//! `commit_stream` keeps one window of matches plus the rsid list in memory,
+//! so its peak RSS is flat in the number of VCF lines, whereas the buffered
+//! path grows with the number of matches (an index and a field element each).
+//! Check with e.g. `/usr/bin/time -v` on the bench binary.
+use std::collections::HashMap;
+
+use ark_bls12_381::{Bls12_381, Fr};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use dna_proofs::{Index, PublicParameters, RsId, RsIdHash, RsIdPolyBuilder};
+
+const LOG_DEGREE: usize = 16;
+
+fn synthetic_vcf(lines: usize) -> String {
+    let mut vcf = String::from("#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n");
+    for i in 0..lines {
+        let alt = ["A", "C", "G", "T"][i % 4];
+        vcf.push_str(&format!("1\t{}\trs{}\tA\t{}\t50\tPASS\t.\n", i + 1, i, alt));
+    }
+    vcf
+}
+
+fn bench_commit_stream(c: &mut Criterion) {
+    let pp = PublicParameters::<Bls12_381>::from_seed([0; 32], LOG_DEGREE);
+    let lines = 1 << LOG_DEGREE;
+    let vcf = synthetic_vcf(lines);
+    let filter = (0..lines)
+        .map(|i| (RsId(i as u64), Index(i)))
+        .collect::<HashMap<_, _>>();
+    let builder = RsIdPolyBuilder::<Fr>::new(filter);
+
+    let mut group = c.benchmark_group("hash");
+    group.sample_size(10);
+    group.bench_function("buffered", |b| {
+        b.iter(|| {
+            let (poly, _) = builder.from_file(vcf.as_bytes());
+            RsIdHash::new(&pp, &poly).unwrap()
+        })
+    });
+    for window in [1 << 10, 1 << 14] {
+        group.bench_with_input(BenchmarkId::new("stream", window), &window, |b, &window| {
+            b.iter(|| builder.commit_stream(&pp, vcf.as_bytes(), window).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_commit_stream);
+criterion_main!(benches);
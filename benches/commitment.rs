@@ -0,0 +1,159 @@
+//! Baselines for setup, committing, proving and verifying. Parameters come
+//! from a fixed seed and values from `test_rng`, so runs are comparable across
+//! machines and commits.
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_std::rand::Rng;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use dna_proofs::{DnaHash, DnaPoly, PointProof, PublicParameters};
+
+const LOG_DEGREE: usize = 16;
+
+/// A sparse polynomial with every `1/density`-th index set to a genotype value.
+fn sparse_polynomial(density: usize) -> (Vec<usize>, Vec<Fr>) {
+    let mut rng = ark_std::test_rng();
+    let indices = (0..1 << LOG_DEGREE).step_by(density).collect::<Vec<_>>();
+    let values = indices
+        .iter()
+        .map(|_| Fr::from(rng.gen_range(0u8..3)))
+        .collect();
+    (indices, values)
+}
+
+fn bench_setup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("setup");
+    group.sample_size(10);
+    for log_degree in [10, 12, 14] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(log_degree),
+            &log_degree,
+            |b, &log_degree| {
+                b.iter(|| PublicParameters::<Bls12_381>::from_seed([0; 32], log_degree))
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_commit_sparse(c: &mut Criterion) {
+    let pp = PublicParameters::<Bls12_381>::from_seed([0; 32], LOG_DEGREE);
+    let mut group = c.benchmark_group("commit_sparse");
+    group.sample_size(10);
+    for density in [1, 10, 100] {
+        let polynomial = sparse_polynomial(density);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("1/{}", density)),
+            &polynomial,
+            |b, polynomial| b.iter(|| pp.commit_sparse(polynomial).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn bench_point_proof(c: &mut Criterion) {
+    let pp = PublicParameters::<Bls12_381>::from_seed([0; 32], LOG_DEGREE);
+    let polynomial = sparse_polynomial(10);
+    let commitment = pp.commit_sparse(&polynomial).unwrap();
+    let (index, value) = (polynomial.0[42], polynomial.1[42]);
+
+    c.bench_function("prove_point_sparse", |b| {
+        b.iter(|| PointProof::new_sparse(&pp, &polynomial, index).unwrap())
+    });
+    let proof = PointProof::new_sparse(&pp, &polynomial, index).unwrap();
+    c.bench_function("verify_point", |b| {
+        b.iter(|| proof.verify(&pp, &commitment, index, value).unwrap())
+    });
+}
+
+/// Committing and opening at 32 indices in one pass, against committing and
+/// proving each index separately.
+fn bench_commit_and_open(c: &mut Criterion) {
+    let pp = PublicParameters::<Bls12_381>::from_seed([0; 32], LOG_DEGREE);
+    let polynomial = sparse_polynomial(10);
+    let indices = polynomial
+        .0
+        .iter()
+        .step_by(200)
+        .take(32)
+        .copied()
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("commit_and_open_32");
+    group.sample_size(10);
+    group.bench_function("separate", |b| {
+        b.iter(|| {
+            let commitment = pp.commit_sparse(&polynomial).unwrap();
+            let proofs = indices
+                .iter()
+                .map(|&index| PointProof::new_sparse(&pp, &polynomial, index).unwrap())
+                .collect::<Vec<_>>();
+            (commitment, proofs)
+        })
+    });
+    group.bench_function("shared", |b| {
+        b.iter(|| PointProof::commit_and_open(&pp, &polynomial, &indices).unwrap())
+    });
+    group.finish();
+}
+
+/// Proving over a 1M-entry sparse polynomial, where gathering the bases is
+/// no longer negligible next to the MSMs.
+fn bench_point_proof_large(c: &mut Criterion) {
+    const LOG_LARGE: usize = 20;
+    let mut rng = ark_std::test_rng();
+    let pp = PublicParameters::<Bls12_381>::from_seed([0; 32], LOG_LARGE);
+    let indices = (0..1 << LOG_LARGE).collect::<Vec<_>>();
+    let values = indices
+        .iter()
+        .map(|_| Fr::from(rng.gen_range(0u8..3)))
+        .collect::<Vec<_>>();
+    let polynomial = (indices, values);
+
+    let mut group = c.benchmark_group("prove_point_sparse_1m");
+    group.sample_size(10);
+    group.bench_function("middle", |b| {
+        b.iter(|| PointProof::new_sparse(&pp, &polynomial, 1 << (LOG_LARGE - 1)).unwrap())
+    });
+    group.finish();
+}
+
+/// Committing to a VCF with a record on every chromosome at every
+/// `1/10`-th position, on thread pools of growing size.
+fn bench_dna_hash(c: &mut Criterion) {
+    let pp = PublicParameters::<Bls12_381>::from_seed([0; 32], LOG_DEGREE);
+    let mut vcf = String::new();
+    for chromosome in (1..=22)
+        .map(|i| i.to_string())
+        .chain(["X".into(), "Y".into()])
+    {
+        for position in (0..1 << LOG_DEGREE).step_by(10) {
+            vcf += &format!("{}\t{}\t.\tA\tG\t50\tPASS\t.\n", chromosome, position);
+        }
+    }
+    let poly = DnaPoly::<Fr>::from_file(vcf.as_bytes());
+
+    let mut group = c.benchmark_group("dna_hash");
+    group.sample_size(10);
+    let mut threads = 1;
+    while threads <= std::thread::available_parallelism().map_or(1, |n| n.get()) {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &poly, |b, poly| {
+            b.iter(|| pool.install(|| DnaHash::new(&pp, poly).unwrap()))
+        });
+        threads *= 2;
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_setup,
+    bench_commit_sparse,
+    bench_point_proof,
+    bench_point_proof_large,
+    bench_commit_and_open,
+    bench_dna_hash
+);
+criterion_main!(benches);
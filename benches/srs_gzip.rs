@@ -0,0 +1,49 @@
+//! Size and load time of the parameters file, plain against gzipped as written
+//! by `init --gzip`. Compressed points are close to uniformly random, so expect
+//! a small size reduction for a noticeable load-time cost.
+use std::io::{Read, Write};
+
+use ark_bls12_381::Bls12_381;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use criterion::{criterion_group, criterion_main, Criterion};
+use dna_proofs::PublicParameters;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+const LOG_DEGREE: usize = 14;
+
+fn bench_srs_gzip(c: &mut Criterion) {
+    let pp = PublicParameters::<Bls12_381>::from_seed([0; 32], LOG_DEGREE);
+    let mut plain = Vec::new();
+    pp.serialize_compressed(&mut plain).unwrap();
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&plain).unwrap();
+    let gzipped = encoder.finish().unwrap();
+    println!(
+        "pp of degree 2^{}: {} bytes plain, {} bytes gzipped ({:.1}%)",
+        LOG_DEGREE,
+        plain.len(),
+        gzipped.len(),
+        100.0 * gzipped.len() as f64 / plain.len() as f64
+    );
+
+    let mut group = c.benchmark_group("srs_load");
+    group.sample_size(10);
+    group.bench_function("plain", |b| {
+        b.iter(|| PublicParameters::<Bls12_381>::deserialize_compressed_unchecked(&plain[..]))
+    });
+    group.bench_function("gzip", |b| {
+        b.iter(|| {
+            let mut bytes = Vec::with_capacity(plain.len());
+            MultiGzDecoder::new(&gzipped[..])
+                .read_to_end(&mut bytes)
+                .unwrap();
+            PublicParameters::<Bls12_381>::deserialize_compressed_unchecked(&bytes[..])
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_srs_gzip);
+criterion_main!(benches);
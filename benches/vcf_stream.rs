@@ -0,0 +1,94 @@
+//! Peak memory of hashing a whole-genome-sized VCF: `DnaPoly::from_file` +
+//! `DnaHash::new`, which hold every record, against `DnaHash::commit_stream`,
+//! which holds one window per chromosome.
+//!
+//! The VCF is generated on the fly, `VCF_BYTES` bytes of it (1 GiB by default),
+//! so that only the parser's memory is measured. Run one mode per process, as
+//! the peak RSS read from `/proc/self/status` covers the whole process:
+//!
+//! ```text
+//! cargo bench --bench vcf_stream -- buffered
+//! cargo bench --bench vcf_stream -- stream
+//! ```
+use std::io::Read;
+use std::time::Instant;
+
+use ark_bls12_381::{Bls12_381, Fr};
+use dna_proofs::{DnaHash, DnaPoly, PublicParameters};
+
+const LOG_DEGREE: usize = 16;
+const WINDOW: usize = 1 << 14;
+
+/// An endless-looking VCF of single-base records spread over chromosomes 1 to
+/// 22, ending after `remaining` bytes at a line boundary.
+struct SyntheticVcf {
+    line: usize,
+    remaining: usize,
+    buffer: Vec<u8>,
+    offset: usize,
+}
+
+impl Read for SyntheticVcf {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.offset == self.buffer.len() {
+            if self.remaining == 0 {
+                return Ok(0);
+            }
+            let chromosome = 1 + self.line % 22;
+            let position = (self.line / 22) % (1 << LOG_DEGREE);
+            let alt = ["A", "C", "G", "T"][self.line % 4];
+            self.buffer = format!(
+                "{}\t{}\trs{}\tA\t{}\t50\tPASS\t.\n",
+                chromosome, position, self.line, alt
+            )
+            .into_bytes();
+            self.remaining = self.remaining.saturating_sub(self.buffer.len());
+            self.offset = 0;
+            self.line += 1;
+        }
+        let n = buf.len().min(self.buffer.len() - self.offset);
+        buf[..n].copy_from_slice(&self.buffer[self.offset..self.offset + n]);
+        self.offset += n;
+        Ok(n)
+    }
+}
+
+/// The peak resident set size, as reported by Linux.
+fn peak_rss() -> Option<String> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    Some(line["VmHWM:".len()..].trim().to_string())
+}
+
+fn main() {
+    let bytes = std::env::var("VCF_BYTES")
+        .ok()
+        .and_then(|bytes| bytes.parse().ok())
+        .unwrap_or(1 << 30);
+    let vcf = SyntheticVcf {
+        line: 0,
+        remaining: bytes,
+        buffer: Vec::new(),
+        offset: 0,
+    };
+    let pp = PublicParameters::<Bls12_381>::from_seed([0; 32], LOG_DEGREE);
+    let baseline = peak_rss();
+
+    // `cargo bench` passes `--bench` along
+    let stream = !std::env::args().any(|arg| arg == "buffered");
+    let start = Instant::now();
+    if stream {
+        DnaHash::<Bls12_381>::commit_stream(&pp, vcf, WINDOW).unwrap();
+    } else {
+        let poly = DnaPoly::<Fr>::from_file(vcf);
+        DnaHash::new(&pp, &poly).unwrap();
+    }
+    println!(
+        "{} over {} bytes: {:.1?}, peak RSS {} (after setup {})",
+        if stream { "stream" } else { "buffered" },
+        bytes,
+        start.elapsed(),
+        peak_rss().unwrap_or_else(|| "unknown".into()),
+        baseline.unwrap_or_else(|| "unknown".into()),
+    );
+}
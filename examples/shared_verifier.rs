@@ -0,0 +1,73 @@
+//! A long-lived verifier: the public parameters are loaded once, shared behind
+//! an `Arc`, and proofs are checked concurrently by a pool of worker threads.
+//!
+//! Requests are read from stdin, one per line, as `<hash> <proof> <index> <value>`
+//! with the hash and proof hex-encoded as printed by the `dna` binary.
+//!
+//!     cargo run --release --example shared_verifier -- pp.bin < requests
+use std::io::{BufRead, BufReader};
+use std::sync::{mpsc, Arc, Mutex};
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_serialize::CanonicalDeserialize;
+use dna_proofs::header::read_curve_id;
+use dna_proofs::{PointProof, PublicParameters, RsIdHash};
+
+const WORKERS: usize = 4;
+
+/// Decodes a hex value behind its curve header, as written by the binary.
+fn decode<T: CanonicalDeserialize>(input: &str) -> Option<T> {
+    let bytes = hex::decode(input).ok()?;
+    let mut bytes = bytes.as_slice();
+    read_curve_id(&mut bytes).ok()?;
+    T::deserialize_compressed(&mut bytes).ok()
+}
+
+fn verify(pp: &PublicParameters<Bls12_381>, request: &str) -> Result<bool, &'static str> {
+    let [hash, proof, index, value] = request.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err("expected <hash> <proof> <index> <value>");
+    };
+    let hash: RsIdHash<Bls12_381> = decode(hash).ok_or("invalid hash")?;
+    let proof: PointProof<Bls12_381> = decode(proof).ok_or("invalid proof")?;
+    let index = index.parse().map_err(|_| "invalid index")?;
+    let value = value.parse::<u64>().map_err(|_| "invalid value")?;
+    let outcome = proof
+        .verify(pp, &hash.commitment(), index, Fr::from(value))
+        .map_err(|_| "index outside the public parameters")?;
+    Ok(outcome.is_valid())
+}
+
+fn main() {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "pp.bin".into());
+    let mut reader = BufReader::new(std::fs::File::open(path).expect("cannot open pp"));
+    read_curve_id(&mut reader).expect("cannot read pp header");
+    let pp = PublicParameters::<Bls12_381>::deserialize_compressed_unchecked(&mut reader)
+        .expect("cannot read pp");
+    let pp = Arc::new(pp);
+
+    let (sender, receiver) = mpsc::channel::<(usize, String)>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    let workers = (0..WORKERS)
+        .map(|_| {
+            let pp = Arc::clone(&pp);
+            let receiver = Arc::clone(&receiver);
+            std::thread::spawn(move || loop {
+                let Ok((line, request)) = receiver.lock().unwrap().recv() else {
+                    break;
+                };
+                match verify(&pp, &request) {
+                    Ok(valid) => println!("{}: {}", line, if valid { "ok" } else { "invalid" }),
+                    Err(error) => println!("{}: error: {}", line, error),
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for (line, request) in std::io::stdin().lock().lines().enumerate() {
+        sender.send((line + 1, request.unwrap())).unwrap();
+    }
+    drop(sender);
+    for worker in workers {
+        worker.join().unwrap();
+    }
+}
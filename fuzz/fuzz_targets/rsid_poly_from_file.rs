@@ -0,0 +1,24 @@
+//! Feeds arbitrary bytes to the VCF parsers, which must not panic whatever the
+//! input. Run with `cargo fuzz run rsid_poly_from_file` from the crate root.
+#![no_main]
+
+use std::collections::HashMap;
+
+use ark_bls12_381::Fr;
+use dna_proofs::{DnaPoly, Index, RsId, RsIdPoly, RsIdPolyBuilder};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // small ids, so that fuzzed rsids actually match and reach the column parsing
+    let filter = (0..16)
+        .map(|i| (RsId(i), Index(i as usize)))
+        .collect::<HashMap<_, _>>();
+
+    let _ = RsIdPoly::<Fr>::from_file(data, filter.clone(), Some(8));
+
+    let builder = RsIdPolyBuilder::<Fr>::new(filter);
+    let _ = builder.qualified_from_file(data);
+    let _ = builder.info_from_file(data, "AF", 1000, Fr::from(0u8));
+
+    let _ = DnaPoly::<Fr>::from_file(data);
+});
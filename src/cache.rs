@@ -0,0 +1,98 @@
+//! An LRU cache of openings, for provers that are asked for the same
+//! disclosure again and again. An opening only depends on the polynomial and
+//! the index, so it is keyed by the digest of the commitment to the polynomial
+//! and the index. Only built with the `proof-cache` feature.
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+use ark_ec::pairing::Pairing;
+use ark_serialize::CanonicalSerialize;
+use lru::LruCache;
+use sha2::{Digest, Sha256};
+
+use crate::commitment::{PointProof, PublicParameters};
+use crate::dna::{Index, RsIdHash, RsIdPoly};
+use crate::error::Error;
+
+/// Openings of [`RsIdHash`]es, keyed by `(commitment digest, index)`.
+pub struct ProofCache<E: Pairing> {
+    proofs: Mutex<LruCache<([u8; 32], usize), PointProof<E>>>,
+}
+
+impl<E: Pairing> ProofCache<E> {
+    /// A cache holding at most `capacity` openings.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            proofs: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.proofs.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn digest<E: Pairing>(hash: &RsIdHash<E>) -> [u8; 32] {
+    let mut bytes = Vec::new();
+    hash.serialize_compressed(&mut bytes).unwrap();
+    Sha256::digest(&bytes).into()
+}
+
+impl<E: Pairing> RsIdHash<E> {
+    /// Like [`Self::prove_checked`], returning the cached opening if `cache` has
+    /// one. `hash` identifies `rsid_poly` in the cache, so an opening is only
+    /// cached once it verifies against `hash`.
+    pub fn prove_cached(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+        hash: &RsIdHash<E>,
+        index: Index,
+        cache: &ProofCache<E>,
+    ) -> Result<PointProof<E>, Error> {
+        let key = (digest(hash), index.0);
+        if let Some(proof) = cache.proofs.lock().unwrap().get(&key) {
+            return Ok(proof.clone());
+        }
+        // the lock is not held while proving, so that other requests proceed
+        let proof = Self::prove_checked(pp, rsid_poly, hash, index)?;
+        cache.proofs.lock().unwrap().put(key, proof.clone());
+        Ok(proof)
+    }
+}
+
+#[test]
+fn test_proof_cache() {
+    type E = ark_bls12_381::Bls12_381;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 6);
+    let filter = crate::dna::read_rsid_list(include_str!("../rsidlist").as_bytes()).unwrap();
+    let (poly, _) = RsIdPoly::from_file(
+        include_str!("../example.vcf").as_bytes(),
+        filter.clone(),
+        None,
+    );
+    let hash = RsIdHash::new(&pp, &poly).unwrap();
+
+    let cache = ProofCache::new(NonZeroUsize::new(2).unwrap());
+    let mut serialized = Vec::new();
+    for index in [0, 1, 0, 2, 0] {
+        let proof = RsIdHash::prove_cached(&pp, &poly, &hash, Index(index), &cache).unwrap();
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        serialized.push(bytes);
+    }
+    assert_eq!(cache.len(), 2);
+    assert_eq!(serialized[0], serialized[2]);
+    assert_eq!(serialized[0], serialized[4]);
+    assert_ne!(serialized[0], serialized[1]);
+
+    // a hash of another polynomial is refused, and does not reach the cache
+    let (empty, _) = RsIdPoly::from_file("".as_bytes(), filter, None);
+    let other = RsIdHash::new(&pp, &empty).unwrap();
+    assert!(RsIdHash::prove_cached(&pp, &poly, &other, Index(3), &cache).is_err());
+    assert!(RsIdHash::prove_cached(&pp, &poly, &hash, Index(3), &cache).is_ok());
+}
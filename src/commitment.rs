@@ -1,34 +1,79 @@
-use std::ops::Deref;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Add, AddAssign, Deref, Sub, SubAssign};
+use core::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 
 use ark_ec::pairing::Pairing;
 use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
-use ark_ff::Field;
+use ark_ff::{One, PrimeField, Zero};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::UniformRand;
 use log::error;
 use rand::{CryptoRng, RngCore};
-use rayon::iter::IndexedParallelIterator;
-use rayon::iter::IntoParallelRefIterator;
-use rayon::prelude::*;
-use rayon::slice::ParallelSliceMut;
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "std")]
+use {
+    ark_ff::Field, rand::SeedableRng, rand_chacha::ChaCha20Rng,
+    rayon::iter::IndexedParallelIterator, rayon::iter::IntoParallelRefIterator, rayon::prelude::*,
+    rayon::slice::ParallelSliceMut,
+};
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct PublicParameters<E: Pairing> {
-    powers_of_g: Vec<E::G1Affine>,
-    powers_of_g2: Vec<E::G2Affine>,
+    pub(crate) powers_of_g: Vec<E::G1Affine>,
+    pub(crate) powers_of_g2: Vec<E::G2Affine>,
 }
 
 impl<E: Pairing> PublicParameters<E> {
+    #[cfg(feature = "std")]
     pub fn new(csrng: &mut (impl RngCore + CryptoRng), log_degree: usize) -> Self {
+        Self::with_generators(
+            csrng,
+            log_degree,
+            E::G1Affine::generator(),
+            E::G2Affine::generator(),
+        )
+        .unwrap()
+    }
+
+    /// Like [`Self::new`], with τ drawn from ChaCha20 seeded with `seed`, so the
+    /// same seed always gives byte-identical parameters. Anyone knowing the seed
+    /// knows τ and can forge proofs: for tests and toy setups only.
+    #[cfg(feature = "std")]
+    pub fn from_seed(seed: [u8; 32], log_degree: usize) -> Self {
+        Self::new(&mut ChaCha20Rng::from_seed(seed), log_degree)
+    }
+
+    /// Like [`Self::new`], with the powers of τ taken over the given generators
+    /// instead of the canonical arkworks ones. Fails unless both are non-zero
+    /// and in the prime-order subgroup.
+    ///
+    /// The generators are the zeroth powers, so proofs against these parameters
+    /// are verified with the same generators.
+    #[cfg(feature = "std")]
+    pub fn with_generators(
+        csrng: &mut (impl RngCore + CryptoRng),
+        log_degree: usize,
+        g: E::G1Affine,
+        h: E::G2Affine,
+    ) -> Result<Self, CommitmentError> {
+        if !in_prime_subgroup(&g) || !in_prime_subgroup(&h) {
+            error!("Generators must be non-zero points of the prime-order subgroup");
+            return Err(CommitmentError::Malformed);
+        }
+
         let chunk_log_size = 12usize;
         let chunk_size = 1 << chunk_log_size;
-        let mut powers_of_g = vec![Default::default(); 1 << log_degree];
+        let mut powers_of_g = alloc::vec![Default::default(); 1 << log_degree];
         let mut powers_of_g2 = Vec::with_capacity(64);
         let tau = E::ScalarField::rand(csrng);
 
-        powers_of_g[0] = E::G1Affine::generator();
+        powers_of_g[0] = g;
         for i in 1..1 << usize::min(log_degree, chunk_log_size) {
-            let current_power = (powers_of_g[i - 1] * &tau).into_affine();
+            let current_power = (powers_of_g[i - 1] * tau).into_affine();
             powers_of_g[i] = current_power;
         }
 
@@ -49,7 +94,7 @@ impl<E: Pairing> PublicParameters<E> {
                 })
         }
 
-        powers_of_g2.push(E::G2Affine::generator());
+        powers_of_g2.push(h);
         for _ in 1..=64 {
             let current_power = (*powers_of_g2.last().unwrap() * tau).into_affine();
             powers_of_g2.push(current_power);
@@ -60,10 +105,204 @@ impl<E: Pairing> PublicParameters<E> {
             .enumerate()
             .for_each(|(i, p)| assert!(!p.is_zero(), "{}", i));
 
-        Self {
+        Ok(Self {
             powers_of_g,
             powers_of_g2,
+        })
+    }
+
+    /// Appends the G1 powers `τ^i·g` for `i` from the current length up to
+    /// `2^new_log_degree`, e.g. as produced by a continued ceremony.
+    ///
+    /// Fails unless `additional_powers` has exactly the missing length and,
+    /// starting from the last existing power, each point is τ times the previous
+    /// one (checked with a single randomized pairing equation).
+    pub fn extend(
+        &mut self,
+        new_log_degree: usize,
+        additional_powers: Vec<E::G1Affine>,
+    ) -> Result<(), CommitmentError> {
+        let old_len = self.powers_of_g.len();
+        let new_len = 1usize
+            .checked_shl(new_log_degree as u32)
+            .filter(|&len| len > old_len)
+            .ok_or(CommitmentError::Malformed)?;
+        if additional_powers.len() != new_len - old_len {
+            error!(
+                "Expected {} additional powers, got {}",
+                new_len - old_len,
+                additional_powers.len()
+            );
+            return Err(CommitmentError::LengthMismatch);
+        }
+
+        let mut chain = Vec::with_capacity(additional_powers.len() + 1);
+        chain.push(self.powers_of_g[old_len - 1]);
+        chain.extend_from_slice(&additional_powers);
+        if additional_powers.iter().any(|p| p.is_zero())
+            || !is_chain::<E>(&chain, self.powers_of_g2[0], self.powers_of_g2[1])
+        {
+            error!("The additional powers do not continue the existing ones");
+            return Err(CommitmentError::CheckFailed);
+        }
+
+        self.powers_of_g.extend(additional_powers);
+        Ok(())
+    }
+
+    /// Checks that each power of τ in G1 is τ times the previous one, with τ taken
+    /// from the G2 powers, returning the index of the first one that is not.
+    ///
+    /// Sound parameters cost a single randomized pairing check; otherwise the
+    /// longest valid prefix is found by bisection. The generators, the zeroth
+    /// powers, must be non-zero points of the prime-order subgroups, or 0 is
+    /// returned. The G2 powers are checked by [`Self::verify_g2`].
+    pub fn verify(&self) -> Result<(), usize> {
+        let (g2, tau_g2) = self.checked_generators()?;
+        let is_valid_prefix = |len: usize| {
+            let powers = &self.powers_of_g[..len];
+            !powers.iter().any(|p| p.is_zero()) && is_chain::<E>(powers, g2, tau_g2)
+        };
+        if is_valid_prefix(self.powers_of_g.len()) {
+            return Ok(());
+        }
+
+        let (mut valid, mut invalid) = (0, self.powers_of_g.len());
+        while invalid - valid > 1 {
+            let mid = (valid + invalid) / 2;
+            if is_valid_prefix(mid) {
+                valid = mid;
+            } else {
+                invalid = mid;
+            }
+        }
+        Err(valid)
+    }
+
+    /// Like [`Self::verify`], checking every pair of consecutive powers with its
+    /// own pairings instead of one randomized check: much slower, for when the
+    /// randomized check is not trusted.
+    #[cfg(feature = "std")]
+    pub fn verify_full(&self) -> Result<(), usize> {
+        let (g2, tau_g2) = self.checked_generators()?;
+        let bad = (1..self.powers_of_g.len())
+            .into_par_iter()
+            .find_first(|&i| {
+                let (previous, power) = (self.powers_of_g[i - 1], self.powers_of_g[i]);
+                power.is_zero() || E::pairing(previous, tau_g2) != E::pairing(power, g2)
+            });
+        bad.map_or(Ok(()), Err)
+    }
+
+    /// Checks that each power of τ in G2 is τ times the previous one, with τ taken
+    /// from the G1 powers, `e(g^τ, h^(τ^(i-1))) == e(g, h^(τ^i))`, returning the
+    /// index of the first one that is not.
+    ///
+    /// Like [`Self::verify`], sound parameters cost a single randomized pairing
+    /// check; only on failure is each power checked on its own.
+    pub fn verify_g2(&self) -> Result<(), usize> {
+        self.checked_generators()?;
+        let (g, tau_g) = match self.powers_of_g[..] {
+            [g, tau_g, ..] => (g, tau_g),
+            _ => return Err(1),
+        };
+        let powers = &self.powers_of_g2;
+        if let Some(i) = powers.iter().position(|p| p.is_zero()) {
+            return Err(i);
+        }
+
+        let mut bytes = Vec::new();
+        if powers.serialize_compressed(&mut bytes).is_err() {
+            return Err(0);
+        }
+        let rho = E::ScalarField::from_le_bytes_mod_order(&Sha256::digest(&bytes));
+        let coefficients = core::iter::successors(Some(E::ScalarField::one()), |r| Some(*r * rho))
+            .take(powers.len() - 1)
+            .collect::<Vec<_>>();
+        let previous = E::G2::msm_unchecked(&powers[..powers.len() - 1], &coefficients);
+        let current = E::G2::msm_unchecked(&powers[1..], &coefficients);
+        if E::pairing(tau_g, previous) == E::pairing(g, current) {
+            return Ok(());
+        }
+        Err((1..powers.len())
+            .find(|&i| E::pairing(tau_g, powers[i - 1]) != E::pairing(g, powers[i]))
+            .unwrap_or(0))
+    }
+
+    /// The G2 generator and τ in G2, `Err(0)` unless there are both and both
+    /// generators are non-zero points of the prime-order subgroups.
+    fn checked_generators(&self) -> Result<(E::G2Affine, E::G2Affine), usize> {
+        match (self.powers_of_g.first(), &self.powers_of_g2[..]) {
+            (Some(g), &[g2, tau_g2, ..]) if in_prime_subgroup(g) && in_prime_subgroup(&g2) => {
+                Ok((g2, tau_g2))
+            }
+            _ => Err(0),
+        }
+    }
+
+    /// Like [`Self::verify`], checking only `samples` random consecutive pairs of
+    /// powers and the pairs at both ends, in one randomized pairing check.
+    ///
+    /// This is a heuristic for quick sanity checks, e.g. in CI, and not a proof
+    /// that the parameters are sound: a single bad power is caught only if it is
+    /// sampled. On failure, the smallest sampled index that does not follow from
+    /// the previous power is returned.
+    #[cfg(feature = "std")]
+    pub fn verify_sampled(&self, samples: usize, rng: &mut impl RngCore) -> Result<(), usize> {
+        let (g2, tau_g2) = self.checked_generators()?;
+        let len = self.powers_of_g.len();
+        if len < 2 {
+            return Ok(());
+        }
+        // each index `i` stands for the pair (i - 1, i)
+        let mut indices = (0..samples)
+            .map(|_| 1 + (rng.next_u64() % (len as u64 - 1)) as usize)
+            .chain([1, len - 1])
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices.dedup();
+        if let Some(&i) = indices.iter().find(|&&i| self.powers_of_g[i].is_zero()) {
+            return Err(i);
+        }
+
+        let coefficients = indices
+            .iter()
+            .map(|_| E::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+        let previous = indices
+            .iter()
+            .map(|&i| self.powers_of_g[i - 1])
+            .collect::<Vec<_>>();
+        let current = indices
+            .iter()
+            .map(|&i| self.powers_of_g[i])
+            .collect::<Vec<_>>();
+        let lhs = E::G1::msm_unchecked(&previous, &coefficients);
+        let rhs = E::G1::msm_unchecked(&current, &coefficients);
+        if E::pairing(lhs, tau_g2) == E::pairing(rhs, g2) {
+            return Ok(());
         }
+        Err(indices
+            .into_iter()
+            .find(|&i| {
+                E::pairing(self.powers_of_g[i - 1], tau_g2) != E::pairing(self.powers_of_g[i], g2)
+            })
+            .unwrap_or(0))
+    }
+
+    /// The G1 and G2 generators these parameters were built over.
+    pub fn generators(&self) -> (E::G1Affine, E::G2Affine) {
+        (self.powers_of_g[0], self.powers_of_g2[0])
+    }
+
+    /// The highest index a [`CommitmentG2`] under these parameters may use.
+    pub fn max_degree_g2(&self) -> usize {
+        self.powers_of_g2.len().saturating_sub(1)
+    }
+
+    /// The highest index a polynomial committed under these parameters may use.
+    pub fn max_degree(&self) -> usize {
+        self.powers_of_g.len().saturating_sub(1)
     }
 
     #[allow(unused)]
@@ -71,22 +310,150 @@ impl<E: Pairing> PublicParameters<E> {
         Commitment::new(self, polynomial)
     }
 
+    /// SHA-256 of the compressed serialization, identifying these parameters.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes).unwrap();
+        Sha256::digest(&bytes).into()
+    }
+
+    /// Commits to a polynomial given as `(indices, values)`; indices not listed
+    /// are treated as 0. `Err` with the first index past
+    /// [`Self::max_degree`], e.g. of an rsid list longer than the setup.
     pub fn commit_sparse(
         &self,
         polynomial: &(
             impl Deref<Target = [usize]>,
             impl Deref<Target = [E::ScalarField]>,
         ),
-    ) -> Commitment<E> {
+    ) -> Result<Commitment<E>, usize> {
         Commitment::new_sparse(self, polynomial)
     }
 
+    /// The base `h` of the blinding term of hiding commitments. It is hashed to
+    /// the curve rather than stored, so that the parameters keep their format
+    /// and nobody knows its discrete logarithm to the powers of `g`.
+    pub fn blinding_generator(&self) -> E::G1Affine {
+        hash_to_g1::<E>(b"dna-proofs/blinding-generator")
+    }
+
+    /// Like [`Self::commit`], adding a random multiple `r·h` of
+    /// [`Self::blinding_generator`] so that the commitment reveals nothing about
+    /// the polynomial. `r` is returned to be kept secret: proving needs it, see
+    /// [`PointProof::new_sparse_hiding`].
+    pub fn commit_hiding(
+        &self,
+        polynomial: &[E::ScalarField],
+        csrng: &mut (impl RngCore + CryptoRng),
+    ) -> (Commitment<E>, Blinding<E>) {
+        Commitment::new(self, polynomial).blind(self, csrng)
+    }
+
+    /// Like [`Self::commit_hiding`], for a sparse polynomial.
+    pub fn commit_sparse_hiding(
+        &self,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        csrng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Commitment<E>, Blinding<E>), usize> {
+        Ok(Commitment::new_sparse(self, polynomial)?.blind(self, csrng))
+    }
+
+    /// Like [`Self::commit_sparse`], rejecting any index at or past `panel_size`;
+    /// see [`PanelBoundProof`] for convincing a verifier of it.
+    pub fn commit_sparse_bounded(
+        &self,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        panel_size: usize,
+    ) -> Result<Commitment<E>, CommitmentError> {
+        if let Some(&i) = polynomial.0.iter().find(|&&i| i >= panel_size) {
+            error!("Index {} is outside the panel of size {}", i, panel_size);
+            return Err(CommitmentError::IndexOutOfRange(i));
+        }
+        self.commit_sparse(polynomial)
+            .map_err(CommitmentError::IndexOutOfRange)
+    }
+
+    /// Like [`Self::commit_sparse`], computing the MSM in chunks of
+    /// [`MSM_CHUNK_SIZE`] terms. Before each chunk `cancel`, if given, is checked
+    /// and the commitment abandoned if it is set; after each chunk `progress` is
+    /// called with the number of terms done so far and the total.
+    pub fn commit_sparse_cancellable(
+        &self,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        cancel: Option<&AtomicBool>,
+        progress: impl FnMut(usize, usize),
+    ) -> Result<Commitment<E>, Aborted> {
+        self.commit_sparse_chunked(polynomial, MSM_CHUNK_SIZE, cancel, progress)
+    }
+
+    fn commit_sparse_chunked(
+        &self,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        chunk_size: usize,
+        cancel: Option<&AtomicBool>,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<Commitment<E>, Aborted> {
+        check_indices(self, &polynomial.0).map_err(Aborted::IndexOutOfRange)?;
+        let total = polynomial.0.len().min(polynomial.1.len());
+        let mut sum = E::G1::zero();
+        let mut done = 0;
+        for (indices, values) in polynomial
+            .0
+            .chunks(chunk_size)
+            .zip(polynomial.1.chunks(chunk_size))
+        {
+            if cancel.is_some_and(|cancel| cancel.load(AtomicOrdering::Relaxed)) {
+                return Err(Aborted::Cancelled);
+            }
+            sum += E::G1::msm_unchecked(
+                &indices
+                    .iter()
+                    .map(|&i| self.powers_of_g[i])
+                    .collect::<Vec<_>>(),
+                values,
+            );
+            done += indices.len();
+            progress(done, total);
+        }
+        Ok(Commitment(sum.into_affine()))
+    }
+
+    /// Commits to a sparse polynomial given as a sequence of windows, keeping
+    /// only one window in memory at a time. Commitments are additive, so this
+    /// equals [`Self::commit_sparse`] of the concatenated windows, including its
+    /// error.
+    pub fn commit_stream<I, V>(
+        &self,
+        windows: impl IntoIterator<Item = (I, V)>,
+    ) -> Result<Commitment<E>, usize>
+    where
+        I: Deref<Target = [usize]>,
+        V: Deref<Target = [E::ScalarField]>,
+    {
+        windows
+            .into_iter()
+            .map(|window| self.commit_sparse(&window))
+            .sum()
+    }
+
     #[allow(unused)]
     pub fn prove_point(
         &self,
         polynomial: &[E::ScalarField],
         index: usize,
-    ) -> Result<PointProof<E>, ()> {
+    ) -> Result<PointProof<E>, CommitmentError> {
         PointProof::new(self, polynomial, index)
     }
 
@@ -98,17 +465,268 @@ impl<E: Pairing> PublicParameters<E> {
             impl Deref<Target = [E::ScalarField]>,
         ),
         index: usize,
-    ) -> Result<PointProof<E>, ()> {
+    ) -> Result<PointProof<E>, CommitmentError> {
         PointProof::new_sparse(self, &polynomial, index)
     }
+
+    /// Checks the pairing relation `e(a, b) == e(c, g2)`.
+    ///
+    /// If `a` commits to `p(X)` and `b = q(τ)·g2` for a polynomial `q` known to the
+    /// verifier, this holds exactly when `c` commits to `p(X)·q(X)`; with a constant
+    /// `q = s` it enforces `c = s·a`. It says nothing about the values `a` or `c`
+    /// open to, and `b` must be derived by the verifier itself from the G2 powers,
+    /// never taken from the prover.
+    pub fn check_product(
+        &self,
+        a: &Commitment<E>,
+        b_g2: &E::G2Affine,
+        c: &Commitment<E>,
+    ) -> Result<(), CommitmentError> {
+        if E::pairing(a.0, *b_g2) == E::pairing(c.0, self.powers_of_g2[0]) {
+            Ok(())
+        } else {
+            Err(CommitmentError::CheckFailed)
+        }
+    }
+
+    /// Commits to a reference genome given as `(indices, values)`: the encoded
+    /// reference allele at each index, under the encoding and indices of the
+    /// genomes it is compared with. Indices not listed hold 0.
+    pub fn reference_commitment(
+        &self,
+        reference: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+    ) -> Result<Commitment<E>, CommitmentError> {
+        if reference.0.len() != reference.1.len() {
+            return Err(CommitmentError::LengthMismatch);
+        }
+        self.commit_sparse(reference)
+            .map_err(CommitmentError::IndexOutOfRange)
+    }
+
+    /// Checks that `commitment` is `reference` plus the sparse `deltas`, i.e. that
+    /// the committed genome differs from the reference only at the listed indices,
+    /// by the listed amounts.
+    ///
+    /// The verifier needs every delta in the clear, so this gives no privacy over
+    /// where or by how much the genome differs from the reference.
+    pub fn verify_delta(
+        &self,
+        commitment: &Commitment<E>,
+        reference: &Commitment<E>,
+        deltas: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+    ) -> Result<(), CommitmentError> {
+        if deltas.0.len() != deltas.1.len() {
+            return Err(CommitmentError::LengthMismatch);
+        }
+        let expected = reference.0.into_group()
+            + self
+                .commit_sparse(deltas)
+                .map_err(CommitmentError::IndexOutOfRange)?
+                .0;
+        if ct_eq(&commitment.0, &expected.into_affine()) {
+            Ok(())
+        } else {
+            Err(CommitmentError::CheckFailed)
+        }
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct Commitment<E: Pairing>(E::G1Affine);
 
-#[derive(CanonicalDeserialize, CanonicalSerialize)]
+#[derive(Clone, CanonicalDeserialize, CanonicalSerialize)]
 pub struct PointProof<E: Pairing>(E::G1Affine, E::G1Affine);
 
+/// An opening checked by [`PointProof::batch_verify_across`]: the commitment,
+/// index, claimed value and proof.
+pub type Opening<'a, E> = (
+    &'a Commitment<E>,
+    usize,
+    <E as Pairing>::ScalarField,
+    &'a PointProof<E>,
+);
+
+/// The secret blinding factor `r` of a hiding commitment `C + r·h`, see
+/// [`PublicParameters::commit_hiding`].
+#[derive(Clone, CanonicalDeserialize, CanonicalSerialize)]
+pub struct Blinding<E: Pairing>(E::ScalarField);
+
+/// An opening of several indices at once, see [`PointProof::new_batch`]: the
+/// commitments to the runs of terms before, between and after the opened
+/// indices, one more than there are indices.
+#[derive(Clone, CanonicalDeserialize, CanonicalSerialize)]
+pub struct BatchPointProof<E: Pairing>(Vec<E::G1Affine>);
+
+/// Number of terms [`PublicParameters::commit_sparse_cancellable`] commits to
+/// between checks of its cancellation flag.
+pub const MSM_CHUNK_SIZE: usize = 1 << 16;
+
+/// An MSM over terms pushed one at a time. Bases are gathered into a buffer of
+/// at most `chunk_size` points, reused across chunks, so that the copies
+/// stay cache-sized instead of growing with the polynomial.
+struct ChunkedMsm<E: Pairing> {
+    bases: Vec<E::G1Affine>,
+    scalars: Vec<E::ScalarField>,
+    sum: E::G1,
+    chunk_size: usize,
+}
+
+impl<E: Pairing> ChunkedMsm<E> {
+    fn new(len_hint: usize, chunk_size: usize) -> Self {
+        let capacity = len_hint.min(chunk_size);
+        Self {
+            bases: Vec::with_capacity(capacity),
+            scalars: Vec::with_capacity(capacity),
+            sum: E::G1::zero(),
+            chunk_size,
+        }
+    }
+
+    fn push(&mut self, base: E::G1Affine, scalar: E::ScalarField) {
+        self.bases.push(base);
+        self.scalars.push(scalar);
+        if self.bases.len() == self.chunk_size {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        self.sum += E::G1::msm_unchecked(&self.bases, &self.scalars);
+        self.bases.clear();
+        self.scalars.clear();
+    }
+
+    fn finish(mut self) -> E::G1 {
+        if !self.bases.is_empty() {
+            self.flush();
+        }
+        self.sum
+    }
+}
+
+/// Why [`PublicParameters::commit_sparse_cancellable`] gave up.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Aborted {
+    /// Stopped through the cancellation flag.
+    Cancelled,
+    /// This index is past the parameters; nothing was committed.
+    IndexOutOfRange(usize),
+}
+
+/// Why a commitment, proof or check could not be computed. Details are also
+/// logged where the error is raised.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CommitmentError {
+    /// This index is past the parameters, or outside the panel or polynomial.
+    IndexOutOfRange(usize),
+    /// The indices and values of a sparse polynomial differ in length.
+    LengthMismatch,
+    /// Parameters, generators or a proof that are not well formed.
+    Malformed,
+    /// A decimal that is not a field element below the modulus.
+    InvalidValue,
+    /// A relation the inputs should satisfy does not hold.
+    CheckFailed,
+}
+
+impl fmt::Display for CommitmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::IndexOutOfRange(index) => write!(f, "index {} out of range", index),
+            Self::LengthMismatch => f.write_str("indices and values differ in length"),
+            Self::Malformed => f.write_str("malformed parameters or proof"),
+            Self::InvalidValue => f.write_str("not a field element"),
+            Self::CheckFailed => f.write_str("check failed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CommitmentError {}
+
+/// Outcome of checking a well-formed proof. Malformed inputs (e.g. an index
+/// outside the parameters) are reported as `Err` instead.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VerifyOutcome {
+    Valid,
+    Invalid,
+}
+
+impl VerifyOutcome {
+    pub fn is_valid(self) -> bool {
+        self == Self::Valid
+    }
+}
+
+impl From<bool> for VerifyOutcome {
+    fn from(valid: bool) -> Self {
+        if valid {
+            Self::Valid
+        } else {
+            Self::Invalid
+        }
+    }
+}
+
+/// Proof that a commitment is the merge of two source commitments: the values
+/// at indices present in both sources are disclosed and opened in each, so the
+/// merge is their sum minus this overlap.
+#[derive(CanonicalDeserialize, CanonicalSerialize)]
+pub struct MergeProof<E: Pairing> {
+    overlap: Vec<usize>,
+    values: Vec<E::ScalarField>,
+    proofs_a: Vec<PointProof<E>>,
+    proofs_b: Vec<PointProof<E>>,
+}
+
+/// Proof that a hiding commitment takes the same value at two indices `a < b`,
+/// without revealing it.
+///
+/// Like a [`BatchPointProof`] of the two indices, it holds the commitments to
+/// the runs of terms before, between and after them, each with a random share
+/// of blinding. What is left of the commitment is `v·(g^a + g^b) + t·h` for the
+/// shared value `v` and the remaining blinding `t`; instead of disclosing `v`,
+/// the proof shows knowledge of `v` and `t` with a Schnorr proof, made
+/// non-interactive with Fiat-Shamir.
+#[derive(CanonicalDeserialize, CanonicalSerialize)]
+pub struct SameValueProof<E: Pairing> {
+    runs: Vec<E::G1Affine>,
+    nonce: E::G1Affine,
+    response_value: E::ScalarField,
+    response_blinding: E::ScalarField,
+}
+
+/// Proof that a commitment only uses indices below a declared panel size, so
+/// that no data is hidden past the agreed panel: the commitment shifted up by
+/// `k = max_degree + 1 - panel_size` powers of τ, which only exists within the
+/// parameters if nothing is at or past `panel_size`.
+///
+/// Verifying needs `τ^k` in G2, of which only [`PublicParameters::max_degree_g2`]
+/// are kept: the panel size must be within that many of the parameters' size.
+/// Without a panel size (the default) indices are open-ended, which lets a
+/// list grow without new parameters but leaves the support of a commitment
+/// unconstrained.
+#[derive(Clone, CanonicalDeserialize, CanonicalSerialize)]
+pub struct PanelBoundProof<E: Pairing>(E::G1Affine);
+
+/// A commitment in G2 rather than G1, for verifiers that are cheaper with the
+/// commitment on that side of a pairing.
+///
+/// It is computed over the powers of τ in G2, of which the parameters keep only
+/// 65 (see [`PublicParameters::max_degree_g2`]): indices past that are rejected.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommitmentG2<E: Pairing>(E::G2Affine);
+
+/// Opening of a [`CommitmentG2`], the G2 counterpart of [`PointProof`].
+#[derive(Clone, CanonicalDeserialize, CanonicalSerialize)]
+pub struct PointProofG2<E: Pairing>(E::G2Affine, E::G2Affine);
+
 impl<E: Pairing> Commitment<E> {
     pub fn new(pp: &PublicParameters<E>, polynomial: &[E::ScalarField]) -> Self {
         let commitment = E::G1::msm_unchecked(&pp.powers_of_g, polynomial);
@@ -121,14 +739,112 @@ impl<E: Pairing> Commitment<E> {
             impl Deref<Target = [usize]>,
             impl Deref<Target = [E::ScalarField]>,
         ),
-    ) -> Self {
+    ) -> Result<Self, usize> {
+        check_indices(pp, &polynomial.0)?;
         let basis = polynomial
             .0
             .iter()
             .map(|i| pp.powers_of_g[*i])
             .collect::<Vec<_>>();
         let commitment = E::G1::msm_unchecked(&basis, &polynomial.1);
-        Self(commitment.into())
+        Ok(Self(commitment.into()))
+    }
+
+    /// Adds a fresh blinding term `r·h` to the commitment.
+    fn blind(
+        self,
+        pp: &PublicParameters<E>,
+        csrng: &mut (impl RngCore + CryptoRng),
+    ) -> (Self, Blinding<E>) {
+        let r = E::ScalarField::rand(csrng);
+        let commitment = pp.blinding_generator() * r + self.0;
+        (Self(commitment.into_affine()), Blinding(r))
+    }
+
+    /// Adds the blinding term of `blinding`, recomputing the hiding commitment
+    /// that [`PublicParameters::commit_hiding`] returned together with it.
+    pub fn with_blinding(self, pp: &PublicParameters<E>, blinding: &Blinding<E>) -> Self {
+        Self((pp.blinding_generator() * blinding.0 + self.0).into_affine())
+    }
+
+    /// Shifts the commitment by `H(tag)·g`, so that commitments made for one
+    /// application (e.g. `b"ancestry"`) do not verify in another; see
+    /// [`Self::untagged`] for the verifier's side.
+    ///
+    /// This is domain separation, not unlinkability: the offset is public, so
+    /// anyone knowing both tags can tell that two commitments hide the same
+    /// polynomial.
+    pub fn tagged(&self, pp: &PublicParameters<E>, tag: &[u8]) -> Self {
+        Self((self.0 + pp.powers_of_g[0] * tag_scalar::<E::ScalarField>(tag)).into_affine())
+    }
+
+    /// Removes the offset [`Self::tagged`] added under `tag`, giving back the
+    /// commitment that proofs open.
+    pub fn untagged(&self, pp: &PublicParameters<E>, tag: &[u8]) -> Self {
+        Self(
+            (self.0.into_group() - pp.powers_of_g[0] * tag_scalar::<E::ScalarField>(tag))
+                .into_affine(),
+        )
+    }
+
+    /// Changes the coefficient at `index` from `old_value` to `new_value` in
+    /// place, adding `(new_value - old_value)·g^index` instead of recomputing the
+    /// MSM. Left unchanged if `index` is past the parameters.
+    pub fn update(
+        &mut self,
+        pp: &PublicParameters<E>,
+        index: usize,
+        old_value: E::ScalarField,
+        new_value: E::ScalarField,
+    ) -> Result<(), CommitmentError> {
+        let base = *pp
+            .powers_of_g
+            .get(index)
+            .ok_or(CommitmentError::IndexOutOfRange(index))?;
+        self.0 = (self.0.into_group() + base * (new_value - old_value)).into_affine();
+        Ok(())
+    }
+}
+
+/// The commitment to the coefficient-wise sum of the committed polynomials, so
+/// an index opens to the sum of its values in both. To aggregate, e.g.
+/// per-chromosome hashes into a genome-wide one, the index domains must not
+/// collide, or the values at shared indices are silently added together.
+impl<E: Pairing> Add for Commitment<E> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Commitment((self.0 + other.0).into_affine())
+    }
+}
+
+impl<E: Pairing> AddAssign for Commitment<E> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+/// The commitment to the coefficient-wise difference, e.g. between two genomes
+/// committed over the same index domain: an index opens to 0 where they agree.
+impl<E: Pairing> Sub for Commitment<E> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Commitment((self.0.into_group() - other.0).into_affine())
+    }
+}
+
+impl<E: Pairing> SubAssign for Commitment<E> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<E: Pairing> core::iter::Sum for Commitment<E> {
+    /// The commitment to the sum of the committed polynomials.
+    fn sum<I: Iterator<Item = Self>>(commitments: I) -> Self {
+        let sum = commitments.fold(E::G1::zero(), |sum, commitment| sum + commitment.0);
+        Commitment(sum.into_affine())
     }
 }
 
@@ -141,24 +857,63 @@ impl<E: Pairing> Default for Commitment<E> {
 impl<E: Pairing> serde::Serialize for Commitment<E> {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         let mut bytes = Vec::<u8>::new();
-        self.0.serialize_compressed(&mut bytes[..]).unwrap();
+        self.0
+            .serialize_compressed(&mut bytes)
+            .map_err(serde::ser::Error::custom)?;
         serializer.serialize_bytes(&bytes[..])
     }
 }
 
+/// Reads back the compressed point written by `Serialize`, as bytes or, in
+/// formats without them such as JSON, as a sequence of integers. The point is
+/// checked to be on the curve and in the prime-order subgroup.
+impl<'de, E: Pairing> serde::Deserialize<'de> for Commitment<E> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a compressed curve point")
+            }
+
+            fn visit_bytes<Er: serde::de::Error>(self, bytes: &[u8]) -> Result<Vec<u8>, Er> {
+                Ok(bytes.to_vec())
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Vec<u8>, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(bytes)
+            }
+        }
+
+        let bytes = deserializer.deserialize_bytes(BytesVisitor)?;
+        E::G1Affine::deserialize_compressed(&bytes[..])
+            .map(Self)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 impl<E: Pairing> PointProof<E> {
     pub fn new(
         pp: &PublicParameters<E>,
         polynomial: &[E::ScalarField],
         index: usize,
-    ) -> Result<Self, ()> {
+    ) -> Result<Self, CommitmentError> {
         if index >= polynomial.len() {
             error!(
                 "Index out of bounds: {} requested, polynomial size {}",
                 index,
                 polynomial.len()
             );
-            Err(())
+            Err(CommitmentError::IndexOutOfRange(index))
         } else {
             let lhs = E::G1::msm_unchecked(&pp.powers_of_g[..index], &polynomial[..index]);
             let rhs = E::G1::msm_unchecked(&pp.powers_of_g[index + 1..], &polynomial[index + 1..]);
@@ -173,56 +928,1798 @@ impl<E: Pairing> PointProof<E> {
             impl Deref<Target = [E::ScalarField]>,
         ),
         index: usize,
-    ) -> Result<Self, ()> {
+    ) -> Result<Self, CommitmentError> {
+        Self::new_sparse_chunked(pp, polynomial, index, MSM_CHUNK_SIZE)
+    }
+
+    fn new_sparse_chunked(
+        pp: &PublicParameters<E>,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        index: usize,
+        chunk_size: usize,
+    ) -> Result<Self, CommitmentError> {
+        check_indices(pp, &polynomial.0).map_err(CommitmentError::IndexOutOfRange)?;
         if polynomial.0.len() != polynomial.1.len() {
-            Err(())
+            Err(CommitmentError::LengthMismatch)
         } else {
-            let mut lhs_bases = Vec::new();
-            let mut lhs_scalars = Vec::new();
-            let mut rhs_bases = Vec::new();
-            let mut rhs_scalars = Vec::new();
+            let mut lhs = ChunkedMsm::<E>::new(polynomial.0.len(), chunk_size);
+            let mut rhs = ChunkedMsm::<E>::new(polynomial.0.len(), chunk_size);
 
             for (&i, &x) in polynomial.0.deref().iter().zip(polynomial.1.deref()) {
                 if i < index {
-                    lhs_scalars.push(x);
-                    lhs_bases.push(pp.powers_of_g[i])
+                    lhs.push(pp.powers_of_g[i], x);
                 } else if i > index {
-                    rhs_scalars.push(x);
-                    rhs_bases.push(pp.powers_of_g[i])
+                    rhs.push(pp.powers_of_g[i], x);
                 }
             }
 
-            let lhs = E::G1::msm_unchecked(&lhs_bases, &lhs_scalars);
-            let rhs = E::G1::msm_unchecked(&rhs_bases, &rhs_scalars);
-            Ok(Self(lhs.into(), rhs.into()))
+            Ok(Self(lhs.finish().into(), rhs.finish().into()))
         }
     }
 
-    pub fn verify(
-        &self,
+    /// Like [`Self::new_sparse`], for a commitment made with `blinding` by
+    /// [`PublicParameters::commit_sparse_hiding`]. The blinding term is split
+    /// randomly between `lhs` and `rhs`, so that neither reveals the values on
+    /// its side and [`Self::verify`] checks the proof unchanged.
+    pub fn new_sparse_hiding(
         pp: &PublicParameters<E>,
-        commitment: &Commitment<E>,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
         index: usize,
-        value: E::ScalarField,
-    ) -> Result<(), ()> {
-        let expected = *pp.powers_of_g.get(index).ok_or(())? * value + self.0 + self.1;
-        if commitment.0 == expected.into_affine() {
-            Ok(())
-        } else {
-            Err(())
-        }
+        blinding: &Blinding<E>,
+        csrng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Self, CommitmentError> {
+        let proof = Self::new_sparse(pp, polynomial, index)?;
+        let h = pp.blinding_generator();
+        let s = E::ScalarField::rand(csrng);
+        Ok(Self(
+            (h * s + proof.0).into_affine(),
+            (h * (blinding.0 - s) + proof.1).into_affine(),
+        ))
     }
-}
-
-#[test]
-fn test_crs() {
-    type E = ark_bls12_381::Bls12_381;
 
-    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 13);
-    for i in 1..pp.powers_of_g.len() - 1 {
-        assert_eq!(
-            E::pairing(pp.powers_of_g[i], pp.powers_of_g2[1]),
-            E::pairing(pp.powers_of_g[i + 1], pp.powers_of_g2[0])
-        );
+    /// Opens `polynomial` at each of `indices` at roughly the cost of one MSM over
+    /// it, instead of one per index; proofs are returned in the order of `indices`.
+    ///
+    /// The terms are split into the runs between consecutive opened indices; each
+    /// run is committed once, in parallel, and every proof is assembled from
+    /// prefix and suffix sums of the runs and of the opened terms.
+    #[cfg(feature = "std")]
+    pub fn new_sparse_many(
+        pp: &PublicParameters<E>,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        indices: &[usize],
+    ) -> Result<Vec<Self>, CommitmentError> {
+        Self::commit_and_open(pp, polynomial, indices).map(|(_, proofs)| proofs)
     }
+
+    /// Like [`Self::new_sparse_many`], also returning the commitment to
+    /// `polynomial`, which is the sum of the runs and opened terms it computes
+    /// anyway.
+    #[cfg(feature = "std")]
+    pub fn commit_and_open(
+        pp: &PublicParameters<E>,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        indices: &[usize],
+    ) -> Result<(Commitment<E>, Vec<Self>), CommitmentError> {
+        check_indices(pp, &polynomial.0).map_err(CommitmentError::IndexOutOfRange)?;
+        if polynomial.0.len() != polynomial.1.len() {
+            return Err(CommitmentError::LengthMismatch);
+        }
+        let mut opened = indices.to_vec();
+        opened.sort_unstable();
+        opened.dedup();
+        let k = opened.len();
+        let (runs, at) = msm_runs(pp, polynomial, &opened);
+
+        let total = runs.iter().sum::<E::G1>() + at.iter().sum::<E::G1>();
+        let mut proofs = Vec::with_capacity(k);
+        let mut lhs = E::G1::zero();
+        let mut rhs = total;
+        for m in 0..k {
+            lhs += runs[m];
+            rhs -= runs[m] + at[m];
+            proofs.push(Self(lhs.into_affine(), rhs.into_affine()));
+            lhs += at[m];
+        }
+
+        let proofs = indices
+            .iter()
+            .map(|i| proofs[opened.binary_search(i).unwrap()].clone())
+            .collect();
+        Ok((Commitment(total.into_affine()), proofs))
+    }
+
+    /// Opens `polynomial` at `indices`, which must be strictly increasing and
+    /// within the parameters, in a single proof: each run of terms between
+    /// opened indices is committed once, in parallel.
+    #[cfg(feature = "std")]
+    pub fn new_batch(
+        pp: &PublicParameters<E>,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        indices: &[usize],
+    ) -> Result<BatchPointProof<E>, CommitmentError> {
+        check_indices(pp, &polynomial.0).map_err(CommitmentError::IndexOutOfRange)?;
+        if polynomial.0.len() != polynomial.1.len() {
+            return Err(CommitmentError::LengthMismatch);
+        }
+        check_batch_indices(pp, indices.iter().copied())?;
+        let (runs, _) = msm_runs(pp, polynomial, indices);
+        Ok(BatchPointProof(E::G1::normalize_batch(&runs)))
+    }
+
+    /// Checks `C == value·g^index + lhs + rhs`, returning `Err` only if `index`
+    /// is outside the parameters.
+    ///
+    /// Every input is public, so nothing here branches on secrets; the final
+    /// equality is still compared in constant time over the serialized points, so
+    /// an adversarial proof learns nothing from how long the comparison takes.
+    pub fn verify(
+        &self,
+        pp: &PublicParameters<E>,
+        commitment: &Commitment<E>,
+        index: usize,
+        value: E::ScalarField,
+    ) -> Result<VerifyOutcome, CommitmentError> {
+        let expected = *pp
+            .powers_of_g
+            .get(index)
+            .ok_or(CommitmentError::IndexOutOfRange(index))?
+            * value
+            + self.0
+            + self.1;
+        Ok(ct_eq(&commitment.0, &expected.into_affine()).into())
+    }
+
+    /// The commitment to the polynomial with the opened coefficient zeroed, i.e.
+    /// `commitment - value·g^index` for a valid opening; it can be reused as a
+    /// commitment in its own right.
+    pub fn remainder_commitment(&self) -> Commitment<E> {
+        Commitment((self.0 + self.1).into_affine())
+    }
+
+    /// Like [`Self::verify`], but first rejects a `value` outside `expected_range`,
+    /// the values the encoding in use can produce, before any group operation.
+    pub fn verify_in_range(
+        &self,
+        pp: &PublicParameters<E>,
+        commitment: &Commitment<E>,
+        index: usize,
+        value: E::ScalarField,
+        expected_range: &[E::ScalarField],
+    ) -> Result<VerifyOutcome, CommitmentError> {
+        if !expected_range.contains(&value) {
+            error!("Opened value is outside the expected range");
+            return Ok(VerifyOutcome::Invalid);
+        }
+        self.verify(pp, commitment, index, value)
+    }
+
+    /// Verifies many `(index, value, proof)` openings of one commitment at once.
+    ///
+    /// The individual checks are combined with random coefficients `r_i` into
+    /// `(Σ r_i)·C == Σ r_i·(value_i·g^index_i + lhs_i + rhs_i)`, a single MSM.
+    /// If any opening is invalid this is [`VerifyOutcome::Invalid`] except with
+    /// probability `1/|F|`; it does not tell which one.
+    pub fn batch_verify(
+        pp: &PublicParameters<E>,
+        commitment: &Commitment<E>,
+        openings: &[(usize, E::ScalarField, &PointProof<E>)],
+        rng: &mut impl RngCore,
+    ) -> Result<VerifyOutcome, CommitmentError> {
+        let mut bases = Vec::with_capacity(3 * openings.len());
+        let mut scalars = Vec::with_capacity(3 * openings.len());
+        let mut total = E::ScalarField::zero();
+
+        for &(index, value, proof) in openings {
+            let r = E::ScalarField::rand(rng);
+            bases.extend([
+                *pp.powers_of_g
+                    .get(index)
+                    .ok_or(CommitmentError::IndexOutOfRange(index))?,
+                proof.0,
+                proof.1,
+            ]);
+            scalars.extend([r * value, r, r]);
+            total += r;
+        }
+
+        let expected = E::G1::msm_unchecked(&bases, &scalars);
+        Ok(ct_eq(
+            &(commitment.0 * total).into_affine(),
+            &expected.into_affine(),
+        )
+        .into())
+    }
+
+    /// Like [`Self::batch_verify`], but deterministic: the coefficients are the
+    /// powers of a challenge hashed from the generators, the commitment and every
+    /// opening, so no verifier randomness is needed. If the combined check fails,
+    /// the openings are verified one by one and the position of the first invalid
+    /// one is returned.
+    pub fn verify_many(
+        pp: &PublicParameters<E>,
+        commitment: &Commitment<E>,
+        openings: &[(usize, E::ScalarField, PointProof<E>)],
+    ) -> Result<(), usize> {
+        if let Some(i) = openings
+            .iter()
+            .position(|&(index, _, _)| index >= pp.powers_of_g.len())
+        {
+            return Err(i);
+        }
+
+        // Serializing into a `Vec` cannot fail
+        let mut bytes = Vec::new();
+        (pp.generators(), *commitment)
+            .serialize_compressed(&mut bytes)
+            .unwrap();
+        for (index, value, proof) in openings {
+            bytes.extend((*index as u64).to_le_bytes());
+            value.serialize_compressed(&mut bytes).unwrap();
+            proof.serialize_compressed(&mut bytes).unwrap();
+        }
+        let challenge = Sha256::new()
+            .chain_update(b"dna-proofs/verify_many")
+            .chain_update(&bytes)
+            .finalize();
+        let rho = E::ScalarField::from_le_bytes_mod_order(&challenge);
+
+        let mut bases = Vec::with_capacity(3 * openings.len() + 1);
+        let mut scalars = Vec::with_capacity(3 * openings.len() + 1);
+        let mut r = E::ScalarField::one();
+        let mut total = E::ScalarField::zero();
+        for (index, value, proof) in openings {
+            bases.extend([pp.powers_of_g[*index], proof.0, proof.1]);
+            scalars.extend([r * value, r, r]);
+            total += r;
+            r *= rho;
+        }
+        bases.push(commitment.0);
+        scalars.push(-total);
+        if E::G1::msm_unchecked(&bases, &scalars).is_zero() {
+            return Ok(());
+        }
+
+        Err(openings
+            .iter()
+            .position(|(index, value, proof)| {
+                !matches!(
+                    proof.verify(pp, commitment, *index, *value),
+                    Ok(VerifyOutcome::Valid)
+                )
+            })
+            .unwrap_or(0))
+    }
+
+    /// Like [`Self::batch_verify`], with each opening against its own
+    /// commitment: `Σ r_i·C_i == Σ r_i·(value_i·g^index_i + lhs_i + rhs_i)`.
+    pub fn batch_verify_across(
+        pp: &PublicParameters<E>,
+        openings: &[Opening<E>],
+        rng: &mut impl RngCore,
+    ) -> Result<VerifyOutcome, CommitmentError> {
+        let mut bases = Vec::with_capacity(4 * openings.len());
+        let mut scalars = Vec::with_capacity(4 * openings.len());
+
+        for &(commitment, index, value, proof) in openings {
+            let r = E::ScalarField::rand(rng);
+            bases.extend([
+                *pp.powers_of_g
+                    .get(index)
+                    .ok_or(CommitmentError::IndexOutOfRange(index))?,
+                proof.0,
+                proof.1,
+                commitment.0,
+            ]);
+            scalars.extend([r * value, r, r, -r]);
+        }
+
+        let difference = E::G1::msm_unchecked(&bases, &scalars);
+        Ok(difference.is_zero().into())
+    }
+}
+
+impl<E: Pairing> BatchPointProof<E> {
+    /// Checks `C == Σ value_m·g^index_m + Σ runs`. `Err` if the indices are not
+    /// strictly increasing, are outside the parameters, or do not match the
+    /// number the proof was made for.
+    pub fn verify(
+        &self,
+        pp: &PublicParameters<E>,
+        commitment: &Commitment<E>,
+        openings: &[(usize, E::ScalarField)],
+    ) -> Result<VerifyOutcome, CommitmentError> {
+        check_batch_indices(pp, openings.iter().map(|&(index, _)| index))?;
+        if self.0.len() != openings.len() + 1 {
+            error!(
+                "The batch proof is for {} indices, {} given",
+                self.0.len().saturating_sub(1),
+                openings.len()
+            );
+            return Err(CommitmentError::LengthMismatch);
+        }
+        let (mut bases, mut scalars): (Vec<_>, Vec<_>) = openings
+            .iter()
+            .map(|&(index, value)| (pp.powers_of_g[index], value))
+            .unzip();
+        bases.extend(&self.0);
+        scalars.resize(bases.len(), E::ScalarField::one());
+        let expected = E::G1::msm_unchecked(&bases, &scalars);
+        Ok(ct_eq(&commitment.0, &expected.into_affine()).into())
+    }
+}
+
+/// The first of `indices` past `pp` as the error, before indexing its powers
+/// would panic.
+fn check_indices<E: Pairing>(pp: &PublicParameters<E>, indices: &[usize]) -> Result<(), usize> {
+    match indices.iter().find(|&&i| i > pp.max_degree()) {
+        Some(&i) => {
+            error!(
+                "Index {} is past the maximum degree {} of the public parameters",
+                i,
+                pp.max_degree()
+            );
+            Err(i)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Rejects batch indices that repeat, are out of order or are past `pp`.
+fn check_batch_indices<E: Pairing>(
+    pp: &PublicParameters<E>,
+    indices: impl Iterator<Item = usize>,
+) -> Result<(), CommitmentError> {
+    let mut previous = None;
+    for index in indices {
+        if index >= pp.powers_of_g.len() {
+            error!(
+                "Index out of bounds: {} requested, parameters size {}",
+                index,
+                pp.powers_of_g.len()
+            );
+            return Err(CommitmentError::IndexOutOfRange(index));
+        }
+        if let Some(previous) = previous.replace(index) {
+            if index <= previous {
+                error!(
+                    "Batch indices must be strictly increasing, {} is not",
+                    index
+                );
+                return Err(CommitmentError::Malformed);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Splits the terms of `polynomial` around the sorted, distinct indices of
+/// `opened` and commits to each part in parallel: `runs[m]` to the terms
+/// between `opened[m - 1]` and `opened[m]`, `runs[k]` to those past the last
+/// one, and `at[m]` to the terms at `opened[m]`.
+#[cfg(feature = "std")]
+fn msm_runs<E: Pairing>(
+    pp: &PublicParameters<E>,
+    polynomial: &(
+        impl Deref<Target = [usize]>,
+        impl Deref<Target = [E::ScalarField]>,
+    ),
+    opened: &[usize],
+) -> (Vec<E::G1>, Vec<E::G1>) {
+    let k = opened.len();
+    let mut runs = vec![(Vec::new(), Vec::new()); k + 1];
+    let mut at = vec![(Vec::new(), Vec::new()); k];
+    for (&i, &x) in polynomial.0.deref().iter().zip(polynomial.1.deref()) {
+        let (bases, scalars) = match opened.binary_search(&i) {
+            Ok(m) => &mut at[m],
+            Err(m) => &mut runs[m],
+        };
+        bases.push(pp.powers_of_g[i]);
+        scalars.push(x);
+    }
+    let msm = |(bases, scalars): &(Vec<E::G1Affine>, Vec<E::ScalarField>)| {
+        E::G1::msm_unchecked(bases, scalars)
+    };
+    (
+        runs.par_iter().map(msm).collect(),
+        at.par_iter().map(msm).collect(),
+    )
+}
+
+/// Hashes `domain` to a point of the prime-order subgroup of G1 by try and
+/// increment, so that its discrete logarithm to any other point is unknown.
+fn hash_to_g1<E: Pairing>(domain: &[u8]) -> E::G1Affine {
+    (0u64..)
+        .find_map(|counter| {
+            let mut bytes = Vec::with_capacity(64);
+            for half in 0..2u8 {
+                bytes.extend(
+                    Sha256::new()
+                        .chain_update(domain)
+                        .chain_update(counter.to_le_bytes())
+                        .chain_update([half])
+                        .finalize(),
+                );
+            }
+            let point = E::G1Affine::from_random_bytes(&bytes)?.clear_cofactor();
+            (!point.is_zero()).then_some(point)
+        })
+        .unwrap()
+}
+
+/// `H(tag)`, the multiple of `g` a tagged commitment is offset by.
+fn tag_scalar<F: PrimeField>(tag: &[u8]) -> F {
+    let digest = Sha256::new()
+        .chain_update(b"dna-proofs/application-tag")
+        .chain_update(tag)
+        .finalize();
+    F::from_le_bytes_mod_order(&digest)
+}
+
+/// The MSM over the powers of τ in G2, `Err` if an index is past them.
+fn msm_g2<E: Pairing>(
+    pp: &PublicParameters<E>,
+    terms: impl Iterator<Item = (usize, E::ScalarField)>,
+) -> Result<E::G2, CommitmentError> {
+    let mut bases = Vec::new();
+    let mut scalars = Vec::new();
+    for (i, x) in terms {
+        match pp.powers_of_g2.get(i) {
+            Some(base) => bases.push(*base),
+            None => {
+                error!(
+                    "Index {} is past the {} powers in G2",
+                    i,
+                    pp.powers_of_g2.len()
+                );
+                return Err(CommitmentError::IndexOutOfRange(i));
+            }
+        }
+        scalars.push(x);
+    }
+    Ok(E::G2::msm_unchecked(&bases, &scalars))
+}
+
+impl<E: Pairing> PanelBoundProof<E> {
+    pub fn new_sparse(
+        pp: &PublicParameters<E>,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        panel_size: usize,
+    ) -> Result<Self, CommitmentError> {
+        let shift = panel_shift(pp, panel_size)?;
+        let mut bases = Vec::with_capacity(polynomial.0.len());
+        for &i in polynomial.0.iter() {
+            if i >= panel_size {
+                error!("Index {} is outside the panel of size {}", i, panel_size);
+                return Err(CommitmentError::IndexOutOfRange(i));
+            }
+            bases.push(pp.powers_of_g[i + shift]);
+        }
+        Ok(Self(
+            E::G1::msm_unchecked(&bases, &polynomial.1).into_affine(),
+        ))
+    }
+
+    /// Checks `e(proof, h) == e(C, τ^k·h)`. Openings of `commitment` should
+    /// still be checked to be below `panel_size` on their own.
+    pub fn verify(
+        &self,
+        pp: &PublicParameters<E>,
+        commitment: &Commitment<E>,
+        panel_size: usize,
+    ) -> Result<VerifyOutcome, CommitmentError> {
+        let shift = panel_shift(pp, panel_size)?;
+        let tau_shift = *pp.powers_of_g2.get(shift).ok_or_else(|| {
+            error!(
+                "A panel of size {} is too small for these parameters to bound",
+                panel_size
+            );
+            CommitmentError::Malformed
+        })?;
+        Ok((E::pairing(self.0, pp.powers_of_g2[0]) == E::pairing(commitment.0, tau_shift)).into())
+    }
+}
+
+/// The shift `max_degree + 1 - panel_size` of a [`PanelBoundProof`].
+fn panel_shift<E: Pairing>(
+    pp: &PublicParameters<E>,
+    panel_size: usize,
+) -> Result<usize, CommitmentError> {
+    pp.powers_of_g.len().checked_sub(panel_size).ok_or_else(|| {
+        error!(
+            "A panel of size {} does not fit the {} powers of the parameters",
+            panel_size,
+            pp.powers_of_g.len()
+        );
+        CommitmentError::Malformed
+    })
+}
+
+impl<E: Pairing> CommitmentG2<E> {
+    pub fn new_sparse(
+        pp: &PublicParameters<E>,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+    ) -> Result<Self, CommitmentError> {
+        let terms = polynomial
+            .0
+            .iter()
+            .copied()
+            .zip(polynomial.1.iter().copied());
+        Ok(Self(msm_g2(pp, terms)?.into_affine()))
+    }
+}
+
+impl<E: Pairing> PointProofG2<E> {
+    pub fn new_sparse(
+        pp: &PublicParameters<E>,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        index: usize,
+    ) -> Result<Self, CommitmentError> {
+        if polynomial.0.len() != polynomial.1.len() {
+            return Err(CommitmentError::LengthMismatch);
+        }
+        let terms = || {
+            polynomial
+                .0
+                .iter()
+                .copied()
+                .zip(polynomial.1.iter().copied())
+        };
+        let lhs = msm_g2(pp, terms().filter(|&(i, _)| i < index))?;
+        let rhs = msm_g2(pp, terms().filter(|&(i, _)| i > index))?;
+        Ok(Self(lhs.into_affine(), rhs.into_affine()))
+    }
+
+    /// Like [`PointProof::verify`], in G2.
+    pub fn verify(
+        &self,
+        pp: &PublicParameters<E>,
+        commitment: &CommitmentG2<E>,
+        index: usize,
+        value: E::ScalarField,
+    ) -> Result<VerifyOutcome, CommitmentError> {
+        let expected = *pp
+            .powers_of_g2
+            .get(index)
+            .ok_or(CommitmentError::IndexOutOfRange(index))?
+            * value
+            + self.0
+            + self.1;
+        Ok(ct_eq(&commitment.0, &expected.into_affine()).into())
+    }
+}
+
+impl<E: Pairing> SameValueProof<E> {
+    /// Proves that `polynomial`, committed with `blinding` by
+    /// [`PublicParameters::commit_sparse_hiding`], has the same value at
+    /// `index_a` and `index_b`; `Err` if it does not.
+    pub fn new_sparse(
+        pp: &PublicParameters<E>,
+        polynomial: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        index_a: usize,
+        index_b: usize,
+        blinding: &Blinding<E>,
+        csrng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Self, CommitmentError> {
+        check_indices(pp, &polynomial.0).map_err(CommitmentError::IndexOutOfRange)?;
+        check_indices(pp, &[index_a, index_b]).map_err(CommitmentError::IndexOutOfRange)?;
+        if index_a == index_b {
+            return Err(CommitmentError::Malformed);
+        }
+        if polynomial.0.len() != polynomial.1.len() {
+            return Err(CommitmentError::LengthMismatch);
+        }
+        let (low, high) = (index_a.min(index_b), index_a.max(index_b));
+
+        let mut runs = alloc::vec![(Vec::new(), Vec::new()); 3];
+        let mut value_low = E::ScalarField::zero();
+        let mut value_high = E::ScalarField::zero();
+        for (&i, &x) in polynomial.0.deref().iter().zip(polynomial.1.deref()) {
+            let run = match i {
+                _ if i == low => {
+                    value_low = x;
+                    continue;
+                }
+                _ if i == high => {
+                    value_high = x;
+                    continue;
+                }
+                _ if i < low => &mut runs[0],
+                _ if i < high => &mut runs[1],
+                _ => &mut runs[2],
+            };
+            run.0.push(pp.powers_of_g[i]);
+            run.1.push(x);
+        }
+        if value_low != value_high {
+            error!(
+                "Values at indices {} and {} differ, cannot prove equality",
+                index_a, index_b
+            );
+            return Err(CommitmentError::CheckFailed);
+        }
+
+        let h = pp.blinding_generator();
+        let mut remaining = blinding.0;
+        let runs = runs
+            .iter()
+            .map(|(bases, scalars)| {
+                let share = E::ScalarField::rand(csrng);
+                remaining -= share;
+                (E::G1::msm_unchecked(bases, scalars) + h * share).into_affine()
+            })
+            .collect::<Vec<_>>();
+
+        let base = pp.powers_of_g[low] + pp.powers_of_g[high];
+        let remainder = base * value_low + h * remaining;
+        let (nonce_value, nonce_blinding) =
+            (E::ScalarField::rand(csrng), E::ScalarField::rand(csrng));
+        let nonce = (base * nonce_value + h * nonce_blinding).into_affine();
+        let challenge = same_value_challenge(pp, low, high, &runs, remainder, nonce);
+        Ok(Self {
+            runs,
+            nonce,
+            response_value: nonce_value + challenge * value_low,
+            response_blinding: nonce_blinding + challenge * remaining,
+        })
+    }
+
+    /// Checks the Schnorr proof `s_v·(g^a + g^b) + s_t·h == T + c·D` for the
+    /// remainder `D`, the commitment minus the runs.
+    pub fn verify(
+        &self,
+        pp: &PublicParameters<E>,
+        commitment: &Commitment<E>,
+        index_a: usize,
+        index_b: usize,
+    ) -> Result<VerifyOutcome, CommitmentError> {
+        if index_a == index_b || self.runs.len() != 3 {
+            return Err(CommitmentError::Malformed);
+        }
+        let (low, high) = (index_a.min(index_b), index_a.max(index_b));
+        let power = |i| {
+            pp.powers_of_g
+                .get(i)
+                .copied()
+                .ok_or(CommitmentError::IndexOutOfRange(i))
+        };
+        let base = power(low)? + power(high)?;
+
+        let remainder = commitment.0.into_group() - self.runs.iter().copied().sum::<E::G1>();
+        let challenge = same_value_challenge(pp, low, high, &self.runs, remainder, self.nonce);
+        let lhs = base * self.response_value + pp.blinding_generator() * self.response_blinding;
+        let rhs = self.nonce + remainder * challenge;
+        Ok(ct_eq(&lhs.into_affine(), &rhs.into_affine()).into())
+    }
+}
+
+/// The Fiat-Shamir challenge of a [`SameValueProof`], over everything the
+/// verifier sees.
+fn same_value_challenge<E: Pairing>(
+    pp: &PublicParameters<E>,
+    low: usize,
+    high: usize,
+    runs: &[E::G1Affine],
+    remainder: E::G1,
+    nonce: E::G1Affine,
+) -> E::ScalarField {
+    // Serializing into a `Vec` cannot fail
+    let mut bytes = Vec::new();
+    pp.generators().serialize_compressed(&mut bytes).unwrap();
+    bytes.extend((low as u64).to_le_bytes());
+    bytes.extend((high as u64).to_le_bytes());
+    runs.serialize_compressed(&mut bytes).unwrap();
+    remainder
+        .into_affine()
+        .serialize_compressed(&mut bytes)
+        .unwrap();
+    nonce.serialize_compressed(&mut bytes).unwrap();
+    let digest = Sha256::new()
+        .chain_update(b"dna-proofs/same-value")
+        .chain_update(&bytes)
+        .finalize();
+    E::ScalarField::from_le_bytes_mod_order(&digest)
+}
+
+/// Parses a decimal integer into a field element. Anything but a plain run of
+/// ASCII digits, and any value not below the field modulus, is rejected rather
+/// than silently reduced.
+pub fn field_from_decimal<F: PrimeField>(s: &str) -> Result<F, CommitmentError> {
+    if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) {
+        error!("Not a decimal integer: {:?}", s);
+        return Err(CommitmentError::InvalidValue);
+    }
+
+    let modulus = F::MODULUS;
+    let modulus = modulus.as_ref();
+    let mut limbs = alloc::vec![0u64; modulus.len()];
+    for digit in s.bytes().map(|c| c - b'0') {
+        let mut carry = u128::from(digit);
+        for limb in limbs.iter_mut() {
+            let x = u128::from(*limb) * 10 + carry;
+            *limb = x as u64;
+            carry = x >> 64;
+        }
+        if carry != 0 {
+            error!("Value {} is not below the field modulus", s);
+            return Err(CommitmentError::InvalidValue);
+        }
+    }
+    if limbs.iter().rev().cmp(modulus.iter().rev()) != Ordering::Less {
+        error!("Value {} is not below the field modulus", s);
+        return Err(CommitmentError::InvalidValue);
+    }
+
+    let bytes = limbs
+        .iter()
+        .flat_map(|limb| limb.to_le_bytes())
+        .collect::<Vec<_>>();
+    Ok(F::from_le_bytes_mod_order(&bytes))
+}
+
+/// Maps a signed integer into the field, a negative `x` as `-|x|`.
+///
+/// Negatives wrap around the modulus: `-1` is `p - 1`, the largest element.
+/// Range checks on opened values must therefore compare field elements (e.g.
+/// membership in `{-1, 0, 1}`) rather than order them as integers.
+pub fn field_from_signed<F: PrimeField>(x: i64) -> F {
+    let magnitude = F::from(x.unsigned_abs());
+    if x < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Like [`field_from_decimal`], also accepting a leading `-`; negatives map as in
+/// [`field_from_signed`], and their magnitude must be below the modulus too.
+pub fn field_from_signed_decimal<F: PrimeField>(s: &str) -> Result<F, CommitmentError> {
+    match s.strip_prefix('-') {
+        Some(magnitude) => field_from_decimal::<F>(magnitude).map(|x| -x),
+        None => field_from_decimal(s),
+    }
+}
+
+/// Collects a sparse polynomial into an index → value map, summing repeats.
+fn to_map<F: PrimeField>(
+    polynomial: &(impl Deref<Target = [usize]>, impl Deref<Target = [F]>),
+) -> BTreeMap<usize, F> {
+    let mut map = BTreeMap::new();
+    for (&i, &x) in polynomial.0.deref().iter().zip(polynomial.1.deref()) {
+        *map.entry(i).or_insert_with(F::zero) += x;
+    }
+    map
+}
+
+impl<E: Pairing> MergeProof<E> {
+    /// Proves that `merged` is the union of `a` and `b`. An index present in both
+    /// must have the same value in both and appears once in `merged`; a conflict
+    /// is an error rather than being summed.
+    pub fn new_sparse(
+        pp: &PublicParameters<E>,
+        a: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        b: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+        merged: &(
+            impl Deref<Target = [usize]>,
+            impl Deref<Target = [E::ScalarField]>,
+        ),
+    ) -> Result<Self, CommitmentError> {
+        if a.0.len() != a.1.len() || b.0.len() != b.1.len() || merged.0.len() != merged.1.len() {
+            return Err(CommitmentError::LengthMismatch);
+        }
+        let map_a = to_map(a);
+        let map_b = to_map(b);
+
+        let mut union = map_a.clone();
+        let mut overlap = Vec::new();
+        let mut values = Vec::new();
+        for (&i, &x) in &map_b {
+            match map_a.get(&i) {
+                Some(&y) if y != x => {
+                    error!("Conflicting values at index {} in the merged sources", i);
+                    return Err(CommitmentError::CheckFailed);
+                }
+                Some(_) => {
+                    overlap.push(i);
+                    values.push(x);
+                }
+                None => {
+                    union.insert(i, x);
+                }
+            }
+        }
+        if to_map(merged) != union {
+            error!("The merged polynomial is not the union of its sources");
+            return Err(CommitmentError::CheckFailed);
+        }
+
+        let proofs = overlap
+            .iter()
+            .map(|&i| {
+                Ok((
+                    PointProof::new_sparse(pp, a, i)?,
+                    PointProof::new_sparse(pp, b, i)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, CommitmentError>>()?;
+        let (proofs_a, proofs_b) = proofs.into_iter().unzip();
+        Ok(Self {
+            overlap,
+            values,
+            proofs_a,
+            proofs_b,
+        })
+    }
+
+    /// Checks that `merged == a + b - Σ v_i·g^{i}` over the disclosed overlap,
+    /// with each `v_i` opened in both `a` and `b`.
+    ///
+    /// This binds the merge to the overlap the prover disclosed; indices shared
+    /// by the sources but left undisclosed would be summed, which
+    /// [`Self::new_sparse`] never does.
+    pub fn verify(
+        &self,
+        pp: &PublicParameters<E>,
+        a: &Commitment<E>,
+        b: &Commitment<E>,
+        merged: &Commitment<E>,
+    ) -> Result<VerifyOutcome, CommitmentError> {
+        let n = self.overlap.len();
+        if self.values.len() != n || self.proofs_a.len() != n || self.proofs_b.len() != n {
+            return Err(CommitmentError::Malformed);
+        }
+
+        let mut valid = true;
+        let mut bases = Vec::with_capacity(n);
+        for (k, &i) in self.overlap.iter().enumerate() {
+            let value = self.values[k];
+            valid &= self.proofs_a[k].verify(pp, a, i, value)?.is_valid();
+            valid &= self.proofs_b[k].verify(pp, b, i, value)?.is_valid();
+            bases.push(pp.powers_of_g[i]);
+        }
+        let shared = E::G1::msm_unchecked(&bases, &self.values);
+        let expected = a.0.into_group() + b.0 - shared;
+        valid &= ct_eq(&merged.0, &expected.into_affine());
+        Ok(valid.into())
+    }
+}
+
+/// Checks that each point is τ times the previous one, given `g2` and `τ·g2`.
+///
+/// All consecutive pairs are folded with powers of a challenge `ρ` derived from the
+/// points into `e(Σ ρ^i·p_i, τ·g2) == e(Σ ρ^i·p_{i+1}, g2)`.
+fn is_chain<E: Pairing>(points: &[E::G1Affine], g2: E::G2Affine, tau_g2: E::G2Affine) -> bool {
+    if points.len() < 2 {
+        return true;
+    }
+
+    let mut bytes = Vec::new();
+    if points.serialize_compressed(&mut bytes).is_err() {
+        return false;
+    }
+    let rho = E::ScalarField::from_le_bytes_mod_order(&Sha256::digest(&bytes));
+    let coefficients = core::iter::successors(Some(E::ScalarField::one()), |r| Some(*r * rho))
+        .take(points.len() - 1)
+        .collect::<Vec<_>>();
+
+    let lhs = E::G1::msm_unchecked(&points[..points.len() - 1], &coefficients);
+    let rhs = E::G1::msm_unchecked(&points[1..], &coefficients);
+    E::pairing(lhs, tau_g2) == E::pairing(rhs, g2)
+}
+
+fn in_prime_subgroup<P: AffineRepr>(point: &P) -> bool {
+    !point.is_zero() && point.mul_bigint(P::ScalarField::MODULUS).is_zero()
+}
+
+/// Compares two values through their compressed encodings without
+/// short-circuiting on the first differing byte.
+fn ct_eq<T: CanonicalSerialize>(a: &T, b: &T) -> bool {
+    let mut a_bytes = Vec::new();
+    let mut b_bytes = Vec::new();
+    if a.serialize_compressed(&mut a_bytes).is_err()
+        || b.serialize_compressed(&mut b_bytes).is_err()
+    {
+        return false;
+    }
+    let diff = a_bytes
+        .iter()
+        .zip(&b_bytes)
+        .fold(0u8, |acc, (x, y)| core::hint::black_box(acc | (x ^ y)));
+    a_bytes.len() == b_bytes.len() && diff == 0
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_crs() {
+    type E = ark_bls12_381::Bls12_381;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 13);
+    for i in 1..pp.powers_of_g.len() - 1 {
+        assert_eq!(
+            E::pairing(pp.powers_of_g[i], pp.powers_of_g2[1]),
+            E::pairing(pp.powers_of_g[i + 1], pp.powers_of_g2[0])
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_verify_parameters() {
+    type E = ark_bls12_381::Bls12_381;
+
+    let mut pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 6);
+    assert_eq!(pp.verify(), Ok(()));
+
+    pp.powers_of_g[37] = pp.powers_of_g[36];
+    assert_eq!(pp.verify(), Err(37));
+    pp.powers_of_g[5] = <E as Pairing>::G1Affine::zero();
+    assert_eq!(pp.verify(), Err(5));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_commitment_arithmetic() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 3);
+    let a = (vec![0, 2], vec![F::from(1u8), F::from(2u8)]);
+    let b = (vec![2, 5], vec![F::from(1u8), F::from(2u8)]);
+    let sum = (
+        vec![0, 2, 5],
+        vec![F::from(1u8), F::from(3u8), F::from(2u8)],
+    );
+    let (ca, cb) = (pp.commit_sparse(&a).unwrap(), pp.commit_sparse(&b).unwrap());
+    assert_eq!(ca + cb, pp.commit_sparse(&sum).unwrap());
+
+    // an opening of the summed polynomial verifies against the summed commitment
+    let proof = PointProof::new_sparse(&pp, &sum, 2).unwrap();
+    assert_eq!(
+        proof.verify(&pp, &(ca + cb), 2, F::from(1u8) + F::from(2u8)),
+        Ok(VerifyOutcome::Valid)
+    );
+    let proof = PointProof::new_sparse(&pp, &sum, 5).unwrap();
+    assert_eq!(
+        proof.verify(&pp, &(ca + cb), 5, F::from(2u8)),
+        Ok(VerifyOutcome::Valid)
+    );
+
+    let mut c = ca;
+    c += cb;
+    c -= cb;
+    assert_eq!(c, ca);
+    assert_eq!(ca - ca, Commitment::default());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_commitment_serde() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 3);
+    let commitment = pp.commit(&[2u8, 0, 1].map(F::from));
+
+    let json = serde_json::to_string(&commitment).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Commitment<E>>(&json).unwrap(),
+        commitment
+    );
+    let bytes = bincode::serialize(&commitment).unwrap();
+    assert_eq!(
+        bincode::deserialize::<Commitment<E>>(&bytes).unwrap(),
+        commitment
+    );
+    // the 48 bytes of a compressed point, after bincode's length prefix
+    assert_eq!(bytes.len(), 8 + 48);
+    assert!(bincode::deserialize::<Commitment<E>>(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_from_seed() {
+    type E = ark_bls12_381::Bls12_381;
+
+    let serialized = |pp: PublicParameters<E>| {
+        let mut bytes = Vec::new();
+        pp.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    };
+    let pp = serialized(PublicParameters::<E>::from_seed([7; 32], 4));
+    assert_eq!(pp, serialized(PublicParameters::from_seed([7; 32], 4)));
+    assert_ne!(pp, serialized(PublicParameters::from_seed([8; 32], 4)));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_verify_parameters_full() {
+    type E = ark_bls12_381::Bls12_381;
+
+    let mut pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 6);
+    assert_eq!(pp.verify_full(), Ok(()));
+    assert_eq!(pp.verify_g2(), Ok(()));
+
+    pp.powers_of_g2[40] = pp.powers_of_g2[39];
+    assert_eq!(pp.verify(), Ok(()));
+    assert_eq!(pp.verify_g2(), Err(40));
+
+    pp.powers_of_g[50] = pp.powers_of_g[49];
+    assert_eq!(pp.verify_full(), Err(50));
+    pp.powers_of_g[0] = <E as Pairing>::G1Affine::zero();
+    assert_eq!(pp.verify(), Err(0));
+    assert_eq!(pp.verify_full(), Err(0));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_verify_sampled() {
+    type E = ark_bls12_381::Bls12_381;
+
+    let mut rng = rand::thread_rng();
+    let mut pp = PublicParameters::<E>::new(&mut rng, 6);
+    assert_eq!(pp.verify_sampled(8, &mut rng), Ok(()));
+
+    // the last power is always sampled
+    pp.powers_of_g[63] = pp.powers_of_g[62];
+    assert_eq!(pp.verify_sampled(0, &mut rng), Err(63));
+    // and with every pair sampled, so is any other
+    pp.powers_of_g[20] = pp.powers_of_g[19];
+    assert_eq!(pp.verify_sampled(1 << 12, &mut rng), Err(20));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_same_value() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let rng = &mut rand::thread_rng();
+    let pp = PublicParameters::<E>::new(rng, 4);
+    let polynomial = (
+        vec![1, 3, 5, 7],
+        vec![F::from(2u8), F::from(1u8), F::from(2u8), F::from(1u8)],
+    );
+    let (commitment, blinding) = pp.commit_sparse_hiding(&polynomial, rng).unwrap();
+
+    let proof = SameValueProof::new_sparse(&pp, &polynomial, 5, 1, &blinding, rng).unwrap();
+    assert_eq!(
+        proof.verify(&pp, &commitment, 1, 5),
+        Ok(VerifyOutcome::Valid)
+    );
+    assert_eq!(
+        proof.verify(&pp, &commitment, 3, 7),
+        Ok(VerifyOutcome::Invalid)
+    );
+    assert!(proof.verify(&pp, &commitment, 3, 3).is_err());
+
+    // both indices absent, so both open to zero
+    let proof = SameValueProof::new_sparse(&pp, &polynomial, 2, 4, &blinding, rng).unwrap();
+    assert_eq!(
+        proof.verify(&pp, &commitment, 2, 4),
+        Ok(VerifyOutcome::Valid)
+    );
+
+    // the same witness with a different blinding does not verify
+    let (other, _) = pp.commit_sparse_hiding(&polynomial, rng).unwrap();
+    assert_eq!(proof.verify(&pp, &other, 2, 4), Ok(VerifyOutcome::Invalid));
+
+    assert!(SameValueProof::new_sparse(&pp, &polynomial, 1, 3, &blinding, rng).is_err());
+    assert!(SameValueProof::new_sparse(&pp, &polynomial, 2, 5, &blinding, rng).is_err());
+    assert!(SameValueProof::new_sparse(&pp, &polynomial, 1, 16, &blinding, rng).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_commit_stream() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 4);
+    let indices = vec![0, 3, 4, 9, 15];
+    let values = indices
+        .iter()
+        .map(|&i| F::from(i as u64 + 1))
+        .collect::<Vec<_>>();
+    let windows = indices
+        .chunks(2)
+        .zip(values.chunks(2))
+        .map(|(i, v)| (i.to_vec(), v.to_vec()));
+    assert_eq!(
+        pp.commit_stream(windows).unwrap(),
+        pp.commit_sparse(&(indices, values)).unwrap()
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_index_past_degree() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 3);
+    let degree = pp.max_degree();
+    let fits = (vec![0, degree], vec![F::from(1u8), F::from(2u8)]);
+    assert!(pp.commit_sparse(&fits).is_ok());
+
+    let past = (vec![0, degree + 1], vec![F::from(1u8), F::from(2u8)]);
+    assert_eq!(pp.commit_sparse(&past), Err(degree + 1));
+    assert_eq!(pp.commit_stream([past.clone()]), Err(degree + 1));
+    assert!(PointProof::new_sparse(&pp, &past, 0).is_err());
+    assert!(PointProof::commit_and_open(&pp, &past, &[0]).is_err());
+}
+
+/// Parameters over a fixed τ, for tests that need reproducible bytes.
+#[cfg(all(test, feature = "std"))]
+pub(crate) fn golden_parameters() -> PublicParameters<ark_bls12_381::Bls12_381> {
+    type E = ark_bls12_381::Bls12_381;
+
+    let tau = <E as Pairing>::ScalarField::from(0x5eedu64);
+    let g = <E as Pairing>::G1Affine::generator();
+    PublicParameters {
+        powers_of_g: (0..4u64)
+            .map(|i| (g * tau.pow([i])).into_affine())
+            .collect(),
+        powers_of_g2: alloc::vec![<E as Pairing>::G2Affine::generator()],
+    }
+}
+
+/// Pins the wire format of commitments and proofs, which users store: a change
+/// here breaks every proof already handed out.
+#[cfg(feature = "std")]
+#[test]
+fn test_golden_vectors() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = golden_parameters();
+    let polynomial = (
+        alloc::vec![0, 2, 3],
+        alloc::vec![F::from(1u8), F::from(2u8), F::from(1u8)],
+    );
+
+    let commitment = pp.commit_sparse(&polynomial).unwrap();
+    let mut bytes = Vec::new();
+    commitment.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(
+        hex::encode(&bytes),
+        "8e42e86afe589c69f47b0ad59cfa6c25346df78d934c71fcda7ef4c518158ec8\
+         f46bf06d38360d26f8b23fb77522ef24"
+    );
+    assert_eq!(
+        Commitment::<E>::deserialize_compressed(&bytes[..]).unwrap(),
+        commitment
+    );
+
+    let proof = PointProof::new_sparse(&pp, &polynomial, 2).unwrap();
+    let mut bytes = Vec::new();
+    proof.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(
+        hex::encode(&bytes),
+        "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac58\
+         6c55e83ff97a1aeffb3af00adb22c6bb94f7d09ca01ded55eb16ac7df5c66cc4\
+         d30cd0e98fb365a78246e0874a0a64f747d016b04be2e49183ee87d665be8ded"
+    );
+    let proof = PointProof::<E>::deserialize_compressed(&bytes[..]).unwrap();
+    assert_eq!(
+        proof.verify(&pp, &commitment, 2, F::from(2u8)),
+        Ok(VerifyOutcome::Valid)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_field_from_decimal() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    assert_eq!(field_from_decimal::<F>("0"), Ok(F::zero()));
+    assert_eq!(field_from_decimal::<F>("0042"), Ok(F::from(42u8)));
+    assert_eq!(
+        field_from_decimal::<F>("18446744073709551617"),
+        Ok(F::from(u64::MAX) + F::from(2u8))
+    );
+
+    let minus_one = (-F::one()).into_bigint().to_string();
+    assert_eq!(field_from_decimal::<F>(&minus_one), Ok(-F::one()));
+
+    let modulus = F::MODULUS.to_string();
+    assert!(field_from_decimal::<F>(&modulus).is_err());
+    assert!(field_from_decimal::<F>(&format!("1{}", modulus)).is_err());
+    assert!(field_from_decimal::<F>("").is_err());
+    assert!(field_from_decimal::<F>("-1").is_err());
+    assert!(field_from_decimal::<F>("1e3").is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_signed_values() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    assert_eq!(field_from_signed::<F>(-1), -F::one());
+    assert_eq!(field_from_signed::<F>(2), F::from(2u8));
+    assert_eq!(field_from_signed::<F>(i64::MIN), -F::from(1u64 << 63));
+    assert_eq!(field_from_signed_decimal::<F>("-1"), Ok(-F::one()));
+    assert_eq!(field_from_signed_decimal::<F>("1"), Ok(F::one()));
+    assert!(field_from_signed_decimal::<F>("--1").is_err());
+    assert!(field_from_signed_decimal::<F>("-").is_err());
+
+    // reference-relative dosage
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 2);
+    let polynomial = (
+        vec![0, 1, 2],
+        [-1, 0, 1].map(field_from_signed::<F>).to_vec(),
+    );
+    let commitment = pp.commit_sparse(&polynomial).unwrap();
+    let proof = PointProof::new_sparse(&pp, &polynomial, 0).unwrap();
+    let value = field_from_signed_decimal("-1").unwrap();
+    assert_eq!(
+        proof.verify(&pp, &commitment, 0, value),
+        Ok(VerifyOutcome::Valid)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_merge() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 4);
+    let a = (
+        vec![1, 3, 5],
+        vec![F::from(1u8), F::from(2u8), F::from(1u8)],
+    );
+    let b = (vec![5, 8], vec![F::from(1u8), F::from(2u8)]);
+    let merged = (
+        vec![1, 3, 5, 8],
+        vec![F::from(1u8), F::from(2u8), F::from(1u8), F::from(2u8)],
+    );
+    let (ca, cb, cm) = (
+        pp.commit_sparse(&a).unwrap(),
+        pp.commit_sparse(&b).unwrap(),
+        pp.commit_sparse(&merged).unwrap(),
+    );
+
+    let proof = MergeProof::new_sparse(&pp, &a, &b, &merged).unwrap();
+    assert_eq!(proof.verify(&pp, &ca, &cb, &cm), Ok(VerifyOutcome::Valid));
+    assert_eq!(proof.verify(&pp, &cb, &cb, &cm), Ok(VerifyOutcome::Invalid));
+
+    // summing the overlap is not a merge
+    let summed = (
+        vec![1, 3, 5, 8],
+        vec![F::from(1u8), F::from(2u8), F::from(2u8), F::from(2u8)],
+    );
+    assert!(MergeProof::new_sparse(&pp, &a, &b, &summed).is_err());
+
+    let conflicting = (vec![5], vec![F::from(2u8)]);
+    assert!(MergeProof::new_sparse(&pp, &a, &conflicting, &merged).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_commit_cancellable() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+    const CHUNK: usize = 16;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 5);
+    let indices = (0..CHUNK + 10).collect::<Vec<_>>();
+    let values = indices
+        .iter()
+        .map(|&i| F::from(i as u64 % 3))
+        .collect::<Vec<_>>();
+    let polynomial = (indices, values);
+
+    let mut reports = Vec::new();
+    let commitment = pp
+        .commit_sparse_chunked(&polynomial, CHUNK, None, |done, total| {
+            reports.push((done, total))
+        })
+        .unwrap();
+    assert_eq!(commitment, pp.commit_sparse(&polynomial).unwrap());
+    let total = CHUNK + 10;
+    assert_eq!(reports, vec![(CHUNK, total), (total, total)]);
+
+    let cancel = AtomicBool::new(true);
+    assert_eq!(
+        pp.commit_sparse_cancellable(&polynomial, Some(&cancel), |_, _| {}),
+        Err(Aborted::Cancelled)
+    );
+
+    let past = (vec![pp.max_degree() + 1], vec![F::from(1u8)]);
+    assert_eq!(
+        pp.commit_sparse_cancellable(&past, None, |_, _| {}),
+        Err(Aborted::IndexOutOfRange(pp.max_degree() + 1))
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_prove_sparse_chunked() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+    const CHUNK: usize = 8;
+
+    // enough terms on the right of the opened index to span two chunks
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 5);
+    let len = 2 * CHUNK + 10;
+    let polynomial = (
+        (0..len).collect::<Vec<_>>(),
+        (0..len).map(|i| F::from(i as u64 % 3)).collect::<Vec<_>>(),
+    );
+    let commitment = pp.commit_sparse(&polynomial).unwrap();
+    let proof = PointProof::new_sparse_chunked(&pp, &polynomial, 7, CHUNK).unwrap();
+    assert_eq!(
+        proof.verify(&pp, &commitment, 7, F::from(1u8)),
+        Ok(VerifyOutcome::Valid)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_commitment_g2() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 8);
+    assert_eq!(pp.max_degree_g2(), 64);
+    let polynomial = (
+        vec![0, 3, 64],
+        vec![F::from(2u8), F::from(1u8), F::from(2u8)],
+    );
+    let commitment = CommitmentG2::new_sparse(&pp, &polynomial).unwrap();
+    let proof = PointProofG2::new_sparse(&pp, &polynomial, 3).unwrap();
+    assert_eq!(
+        proof.verify(&pp, &commitment, 3, F::from(1u8)),
+        Ok(VerifyOutcome::Valid)
+    );
+    assert_eq!(
+        proof.verify(&pp, &commitment, 3, F::from(2u8)),
+        Ok(VerifyOutcome::Invalid)
+    );
+
+    let too_long = (vec![65], vec![F::from(1u8)]);
+    assert!(CommitmentG2::new_sparse(&pp, &too_long).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_shared_across_threads() {
+    use std::sync::Arc;
+
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<PublicParameters<E>>();
+    assert_send_sync::<Commitment<E>>();
+    assert_send_sync::<PointProof<E>>();
+
+    let pp = Arc::new(PublicParameters::<E>::new(&mut rand::thread_rng(), 5));
+    let polynomial = (
+        (0..32).collect::<Vec<_>>(),
+        (0..32u8).map(|i| F::from(i % 3)).collect::<Vec<_>>(),
+    );
+    let commitment = pp.commit_sparse(&polynomial).unwrap();
+    let proofs = (0..32)
+        .map(|i| PointProof::new_sparse(&pp, &polynomial, i).unwrap())
+        .collect::<Vec<_>>();
+    let proofs = Arc::new(proofs);
+
+    let handles = (0..8)
+        .map(|thread| {
+            let pp = Arc::clone(&pp);
+            let proofs = Arc::clone(&proofs);
+            std::thread::spawn(move || {
+                (thread..32).step_by(8).all(|i| {
+                    let value = F::from(i as u8 % 3);
+                    proofs[i].verify(&pp, &commitment, i, value) == Ok(VerifyOutcome::Valid)
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    assert!(handles.into_iter().all(|handle| handle.join().unwrap()));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_remainder_commitment() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 4);
+    let polynomial = (
+        vec![1, 5, 9],
+        vec![F::from(1u8), F::from(2u8), F::from(1u8)],
+    );
+    let proof = PointProof::new_sparse(&pp, &polynomial, 5).unwrap();
+    let remainder = (vec![1, 9], vec![F::from(1u8), F::from(1u8)]);
+    assert_eq!(
+        proof.remainder_commitment(),
+        pp.commit_sparse(&remainder).unwrap()
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_tagged_commitment() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 3);
+    let polynomial = (vec![0, 2], vec![F::from(1u8), F::from(2u8)]);
+    let commitment = pp.commit_sparse(&polynomial).unwrap();
+    let ancestry = commitment.tagged(&pp, b"ancestry");
+    assert_ne!(ancestry, commitment);
+    assert_ne!(ancestry, commitment.tagged(&pp, b"health"));
+
+    let proof = PointProof::new_sparse(&pp, &polynomial, 0).unwrap();
+    let verify = |tagged: Commitment<E>, tag: &[u8]| {
+        proof.verify(&pp, &tagged.untagged(&pp, tag), 0, F::from(1u8))
+    };
+    assert_eq!(verify(ancestry, b"ancestry"), Ok(VerifyOutcome::Valid));
+    assert_eq!(verify(ancestry, b"health"), Ok(VerifyOutcome::Invalid));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_panel_bound() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 6);
+    let polynomial = (vec![0, 10, 39], vec![F::from(1u8); 3]);
+    let commitment = pp.commit_sparse_bounded(&polynomial, 40).unwrap();
+    let proof = PanelBoundProof::new_sparse(&pp, &polynomial, 40).unwrap();
+    assert_eq!(proof.verify(&pp, &commitment, 40), Ok(VerifyOutcome::Valid));
+    assert_eq!(
+        proof.verify(&pp, &commitment, 41),
+        Ok(VerifyOutcome::Invalid)
+    );
+
+    // hidden data at index 50, past the panel
+    let hiding = (vec![0, 50], vec![F::from(1u8); 2]);
+    assert!(pp.commit_sparse_bounded(&hiding, 40).is_err());
+    assert!(PanelBoundProof::new_sparse(&pp, &hiding, 40).is_err());
+    // a proof for a smaller support does not pass for the larger commitment
+    let hidden = pp.commit_sparse(&hiding).unwrap();
+    let partial = PanelBoundProof::new_sparse(&pp, &(vec![0], vec![F::from(1u8)]), 40).unwrap();
+    assert_eq!(partial.verify(&pp, &hidden, 40), Ok(VerifyOutcome::Invalid));
+    assert!(proof.verify(&pp, &commitment, 65).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_batch_point_proof() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 5);
+    let polynomial = (
+        vec![1, 4, 4, 9, 17, 30],
+        (1..=6u8).map(F::from).collect::<Vec<_>>(),
+    );
+    let commitment = pp.commit_sparse(&polynomial).unwrap();
+
+    let proof = PointProof::new_batch(&pp, &polynomial, &[0, 4, 17, 30]).unwrap();
+    let mut openings = vec![
+        (0, F::zero()),
+        (4, F::from(5u8)),
+        (17, F::from(5u8)),
+        (30, F::from(6u8)),
+    ];
+    assert_eq!(
+        proof.verify(&pp, &commitment, &openings),
+        Ok(VerifyOutcome::Valid)
+    );
+    openings[2].1 = F::from(4u8);
+    assert_eq!(
+        proof.verify(&pp, &commitment, &openings),
+        Ok(VerifyOutcome::Invalid)
+    );
+    assert!(proof.verify(&pp, &commitment, &openings[..3]).is_err());
+    openings.swap(0, 1);
+    assert!(proof.verify(&pp, &commitment, &openings).is_err());
+
+    // duplicate, unsorted and out of range indices
+    assert!(PointProof::new_batch(&pp, &polynomial, &[4, 4]).is_err());
+    assert!(PointProof::new_batch(&pp, &polynomial, &[9, 4]).is_err());
+    assert!(PointProof::new_batch(&pp, &polynomial, &[4, 32]).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hiding_commitment() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 5);
+    let h = pp.blinding_generator();
+    assert!(in_prime_subgroup(&h));
+    assert_eq!(h, pp.blinding_generator());
+
+    let polynomial = (
+        vec![1, 4, 9],
+        vec![F::from(1u8), F::from(2u8), F::from(3u8)],
+    );
+    let (commitment, blinding) = pp
+        .commit_sparse_hiding(&polynomial, &mut rand::thread_rng())
+        .unwrap();
+    assert_ne!(commitment, pp.commit_sparse(&polynomial).unwrap());
+    let (again, _) = pp
+        .commit_sparse_hiding(&polynomial, &mut rand::thread_rng())
+        .unwrap();
+    assert_ne!(commitment, again);
+    assert_eq!(
+        pp.commit_sparse(&polynomial)
+            .unwrap()
+            .with_blinding(&pp, &blinding),
+        commitment
+    );
+
+    let proof =
+        PointProof::new_sparse_hiding(&pp, &polynomial, 4, &blinding, &mut rand::thread_rng())
+            .unwrap();
+    assert_eq!(
+        proof.verify(&pp, &commitment, 4, F::from(2u8)),
+        Ok(VerifyOutcome::Valid)
+    );
+    assert_eq!(
+        proof.verify(&pp, &commitment, 4, F::from(3u8)),
+        Ok(VerifyOutcome::Invalid)
+    );
+    // an unblinded proof does not open the hiding commitment
+    let plain = PointProof::new_sparse(&pp, &polynomial, 4).unwrap();
+    assert_eq!(
+        plain.verify(&pp, &commitment, 4, F::from(2u8)),
+        Ok(VerifyOutcome::Invalid)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_prove_many() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 5);
+    let polynomial = (
+        vec![1, 4, 4, 9, 17, 30],
+        (1..=6u8).map(F::from).collect::<Vec<_>>(),
+    );
+    let commitment = pp.commit_sparse(&polynomial).unwrap();
+
+    // unsorted, repeated, absent and doubly present indices
+    let indices = [17, 0, 4, 30, 4, 1, 12];
+    let proofs = PointProof::new_sparse_many(&pp, &polynomial, &indices).unwrap();
+    assert_eq!(proofs.len(), indices.len());
+    for (&index, proof) in indices.iter().zip(&proofs) {
+        let mut many = Vec::new();
+        let mut single = Vec::new();
+        proof.serialize_compressed(&mut many).unwrap();
+        PointProof::new_sparse(&pp, &polynomial, index)
+            .unwrap()
+            .serialize_compressed(&mut single)
+            .unwrap();
+        assert_eq!(many, single);
+    }
+    assert_eq!(
+        proofs[2].verify(&pp, &commitment, 4, F::from(5u8)),
+        Ok(VerifyOutcome::Valid)
+    );
+    let (opened, _) = PointProof::commit_and_open(&pp, &polynomial, &indices).unwrap();
+    assert_eq!(opened, commitment);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_check_product() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 4);
+    let polynomial = [F::from(1u8), F::from(2u8), F::from(0u8), F::from(1u8)];
+    let scaled = polynomial.map(|x| x * F::from(3u8));
+
+    let a = pp.commit(&polynomial);
+    let c = pp.commit(&scaled);
+    let b = (pp.powers_of_g2[0] * F::from(3u8)).into_affine();
+    assert!(pp.check_product(&a, &b, &c).is_ok());
+    assert!(pp.check_product(&c, &b, &a).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_update() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 3);
+    let mut commitment = pp
+        .commit_sparse(&(vec![0, 2, 5], vec![F::from(1u8); 3]))
+        .unwrap();
+
+    commitment
+        .update(&pp, 2, F::from(1u8), F::from(2u8))
+        .unwrap();
+    commitment
+        .update(&pp, 3, F::from(0u8), F::from(1u8))
+        .unwrap();
+    let expected = pp
+        .commit_sparse(&(
+            vec![0, 2, 3, 5],
+            vec![F::from(1u8), F::from(2u8), F::from(1u8), F::from(1u8)],
+        ))
+        .unwrap();
+    let serialized = |commitment: &Commitment<E>| {
+        let mut bytes = Vec::new();
+        commitment.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    };
+    assert_eq!(serialized(&commitment), serialized(&expected));
+    assert!(commitment
+        .update(&pp, 8, F::from(0u8), F::from(1u8))
+        .is_err());
+    assert_eq!(commitment, expected);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_reference_delta() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 3);
+    let reference = [1u8, 2, 1, 1, 2, 1].map(F::from);
+    let sparse = ((0..6).collect::<Vec<_>>(), reference.to_vec());
+    let reference = pp.reference_commitment(&sparse).unwrap();
+    assert_eq!(reference, pp.commit(&sparse.1));
+    assert_eq!(
+        pp.reference_commitment(&(vec![8], vec![F::from(1u8)])),
+        Err(CommitmentError::IndexOutOfRange(8))
+    );
+
+    let genome = [1u8, 2, 2, 1, 1, 1].map(F::from);
+    let commitment = pp.commit(&genome);
+    let deltas = (vec![2, 4], vec![F::from(1u8), -F::from(1u8)]);
+    assert!(pp.verify_delta(&commitment, &reference, &deltas).is_ok());
+
+    let deltas = (vec![2], vec![F::from(1u8)]);
+    assert!(pp.verify_delta(&commitment, &reference, &deltas).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_batch_verify() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let rng = &mut rand::thread_rng();
+    let pp = PublicParameters::<E>::new(rng, 4);
+    let polynomial = [2u8, 0, 1, 1, 0, 2].map(F::from);
+    let commitment = pp.commit(&polynomial);
+
+    let proofs = (0..6)
+        .map(|i| pp.prove_point(&polynomial, i).unwrap())
+        .collect::<Vec<_>>();
+    let mut openings = proofs
+        .iter()
+        .enumerate()
+        .map(|(i, proof)| (i, polynomial[i], proof))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        PointProof::batch_verify(&pp, &commitment, &openings, rng),
+        Ok(VerifyOutcome::Valid)
+    );
+
+    openings[3].1 = F::from(2u8);
+    assert_eq!(
+        PointProof::batch_verify(&pp, &commitment, &openings, rng),
+        Ok(VerifyOutcome::Invalid)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_verify_many() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::from_seed([0; 32], 4);
+    let polynomial = [2u8, 0, 1, 1, 0, 2].map(F::from);
+    let commitment = pp.commit(&polynomial);
+
+    let mut openings = (0..6)
+        .map(|i| (i, polynomial[i], pp.prove_point(&polynomial, i).unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(PointProof::verify_many(&pp, &commitment, &openings), Ok(()));
+    assert_eq!(PointProof::verify_many(&pp, &commitment, &[]), Ok(()));
+
+    openings[3].1 = F::from(2u8);
+    assert_eq!(PointProof::verify_many(&pp, &commitment, &openings), Err(3));
+    openings[3].1 = polynomial[3];
+    openings[4].0 = 1 << 10;
+    assert_eq!(PointProof::verify_many(&pp, &commitment, &openings), Err(4));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_custom_generators() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let rng = &mut rand::thread_rng();
+    let g = (<E as Pairing>::G1Affine::generator() * F::from(5u8)).into_affine();
+    let h = (<E as Pairing>::G2Affine::generator() * F::from(7u8)).into_affine();
+    let pp = PublicParameters::<E>::with_generators(rng, 3, g, h).unwrap();
+    assert_eq!(pp.generators(), (g, h));
+
+    let polynomial = [1u8, 2, 0, 1].map(F::from);
+    let commitment = pp.commit(&polynomial);
+    let proof = pp.prove_point(&polynomial, 1).unwrap();
+    assert_eq!(
+        proof.verify(&pp, &commitment, 1, F::from(2u8)),
+        Ok(VerifyOutcome::Valid)
+    );
+
+    let zero = <E as Pairing>::G1Affine::zero();
+    assert!(PublicParameters::<E>::with_generators(rng, 3, zero, h).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_extend() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let tau = F::from(3u8);
+    let g = <E as Pairing>::G1Affine::generator();
+    let h = <E as Pairing>::G2Affine::generator();
+    let powers = (0..16u64)
+        .map(|i| (g * tau.pow([i])).into_affine())
+        .collect::<Vec<_>>();
+
+    let mut pp = PublicParameters::<E> {
+        powers_of_g: powers[..8].to_vec(),
+        powers_of_g2: vec![h, (h * tau).into_affine()],
+    };
+    let mut tampered = powers[8..].to_vec();
+    tampered[3] = g;
+    assert!(pp.extend(4, tampered).is_err());
+    assert!(pp.extend(4, powers[8..12].to_vec()).is_err());
+    assert!(pp.extend(4, powers[8..].to_vec()).is_ok());
+    assert_eq!(pp.powers_of_g, powers);
 }
@@ -1,12 +1,27 @@
-use crate::commitment::{Commitment, PointProof, PublicParameters};
+use crate::commitment::{
+    field_from_signed_decimal, Blinding, Commitment, CommitmentError, MergeProof, PointProof,
+    PublicParameters, SameValueProof, VerifyOutcome,
+};
+use crate::envelope::{ALLELE_HASH_ENCODING, BASE_ENCODING, DISTINCT_BASE_ENCODING};
+use crate::error::Error;
 use ark_ec::pairing::Pairing;
+use ark_ff::{Field, PrimeField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read};
-use std::borrow::Borrow;
-use std::collections::HashMap;
-use std::fmt::Debug;
+use log::{error, warn};
+use rand::{CryptoRng, RngCore};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug};
 use std::io::{BufRead, BufReader};
+use std::str::FromStr;
 
-pub(crate) fn base_to_int(base: &[u8]) -> u8 {
+/// Encodes an ALT allele, as [`BASE_ENCODING`]. Complementary bases share a
+/// value, A and T 1, C and G 2, so that a value does not depend on the strand a
+/// call is reported on, but an A>C substitution commits like an A>G one; see
+/// [`distinct_base_to_int`] to tell them apart. Symbolic alleles such as gVCF's
+/// `<NON_REF>` carry no called base and encode as 0, like the reference.
+pub fn base_to_int(base: &[u8]) -> u8 {
     match base {
         b"A" => 1,
         b"C" => 2,
@@ -16,58 +31,618 @@ pub(crate) fn base_to_int(base: &[u8]) -> u8 {
     }
 }
 
-#[allow(unused)]
-fn chromosome_to_int(chr: &[u8]) -> usize {
-    str::parse(std::str::from_utf8(chr).unwrap()).unwrap()
+/// Encodes an ALT allele, as [`DISTINCT_BASE_ENCODING`]: A 1, C 2, G 3 and T 4.
+/// Anything else, `N`, indels and symbolic alleles, encodes as 0.
+pub fn distinct_base_to_int(base: &[u8]) -> u8 {
+    match base {
+        b"A" => 1,
+        b"C" => 2,
+        b"G" => 3,
+        b"T" => 4,
+        _ => 0,
+    }
+}
+
+/// The allele encoding with the given id, as recorded in envelopes and
+/// manifests, for [`RsIdPolyBuilder::with_encoding`]; `None` if unknown, or for
+/// [`ALLELE_HASH_ENCODING`], which also needs REF, see
+/// [`RsIdPolyBuilder::allele_hash`].
+/// Commitments made under different encodings differ, so the id must travel
+/// with them.
+pub fn value_encoding<F: From<u8>>(encoding: u8) -> Option<fn(&[u8]) -> F> {
+    match encoding {
+        BASE_ENCODING => Some(base_encoding::<F>),
+        DISTINCT_BASE_ENCODING => Some(distinct_base_encoding::<F>),
+        _ => None,
+    }
+}
+
+/// The value of an ALT allele under [`ALLELE_HASH_ENCODING`], given its REF.
+///
+/// A single-base substitution encodes as with [`distinct_base_to_int`], 1 to 4.
+/// Anything else, an insertion or deletion (REF and ALT of different lengths),
+/// a multi-base substitution or a symbolic allele such as `<DEL>`, encodes as
+/// SHA-256 of its kind, REF and ALT, reduced into the field.
+///
+/// Telling two such alleles apart thus rests on the collision resistance of
+/// SHA-256 (the reduction keeps about 2^127 work to find a collision), and a
+/// hashed value lands on 0 to 4 only by finding a preimage. A symbolic allele
+/// binds only its symbol and REF base: two `<DEL>`s of different extents at
+/// one rsid commit alike.
+pub fn allele_value<F: PrimeField>(reference: &[u8], alt: &[u8]) -> F {
+    let kind: &[u8] = if alt.starts_with(b"<") {
+        b"symbolic"
+    } else if reference.len() < alt.len() {
+        b"insertion"
+    } else if reference.len() > alt.len() {
+        b"deletion"
+    } else if alt.len() == 1 {
+        return distinct_base_to_int(alt).into();
+    } else {
+        b"substitution"
+    };
+    let digest = Sha256::new()
+        .chain_update(b"dna-proofs/allele")
+        .chain_update([kind.len() as u8])
+        .chain_update(kind)
+        .chain_update((reference.len() as u64).to_le_bytes())
+        .chain_update(reference)
+        .chain_update(alt)
+        .finalize();
+    F::from_le_bytes_mod_order(&digest)
+}
+
+/// Reads a claimed value either as an allele, through the encoding with id
+/// `encoding`, or as a signed decimal field element; `Err` if the decimal is not
+/// below the modulus or the encoding is unknown. Under [`ALLELE_HASH_ENCODING`]
+/// an allele is given with its REF, as `REF>ALT`.
+pub fn parse_value<F: PrimeField>(value: &str, encoding: u8) -> Result<F, Error> {
+    let magnitude = value.strip_prefix('-').unwrap_or(value);
+    if !magnitude.is_empty() && magnitude.bytes().all(|c| c.is_ascii_digit()) {
+        Ok(field_from_signed_decimal(value)?)
+    } else if encoding == ALLELE_HASH_ENCODING {
+        let (reference, alt) = value.split_once('>').ok_or(Error::InvalidValue)?;
+        Ok(allele_value(reference.as_bytes(), alt.as_bytes()))
+    } else {
+        let encoding = value_encoding::<F>(encoding).ok_or(Error::UnknownEncoding(encoding))?;
+        Ok(encoding(value.as_bytes()))
+    }
+}
+
+/// The value a raw data genotype call (`AG`) commits to under `encoding`:
+/// `low + 256·high` for the values of its two alleles, ordered so that `AG` and
+/// `GA` agree. A hemizygous call (`A`, as on a male X) counts as homozygous.
+pub fn genotype_value<F: PrimeField>(encoding: fn(&[u8]) -> F, genotype: &[u8]) -> F {
+    let (first, second) = match genotype {
+        [allele] => (encoding(&[*allele]), encoding(&[*allele])),
+        _ => (encoding(&genotype[..1]), encoding(&genotype[1..])),
+    };
+    let (low, high) = if first.into_bigint() <= second.into_bigint() {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    low + high * F::from(256u16)
+}
+
+/// gVCF appends the symbolic `<NON_REF>` (or `<*>`) allele to every ALT field;
+/// drop it so a called variant encodes as its base. A lone `<NON_REF>` is a
+/// reference block and is left for the encoding to map to the reference.
+fn strip_non_ref(alt: &[u8]) -> &[u8] {
+    alt.strip_suffix(b",<NON_REF>")
+        .or_else(|| alt.strip_suffix(b",<*>"))
+        .unwrap_or(alt)
+}
+
+/// The allele numbers called for the first sample, 0 for the reference, from
+/// the FORMAT and sample columns; `None` if there is no `GT` or the call is
+/// missing, even partly (`./1`).
+fn genotype(cells: &[&[u8]]) -> Option<Vec<usize>> {
+    let field = cells
+        .get(8)?
+        .split(|&b| b == b':')
+        .position(|key| key == b"GT")?;
+    let gt = cells.get(9)?.split(|&b| b == b':').nth(field)?;
+    gt.split(|&b| b == b'/' || b == b'|')
+        .map(parse_cell::<usize>)
+        .collect()
+}
+
+/// The lines of `vcf` as raw bytes: stray non-UTF-8 bytes, e.g. in INFO or
+/// comments, only matter to the columns that get decoded. The lines end at the
+/// first read error, which is logged.
+fn raw_lines(vcf: impl Read) -> impl Iterator<Item = Vec<u8>> {
+    BufReader::new(vcf).split(b'\n').map_while(|line| {
+        line.inspect_err(|e| error!("Stopping at a read error: {}", e))
+            .ok()
+    })
+}
+
+/// Splits a line on whitespace, which also drops a trailing `\r`.
+fn split_cells(line: &[u8]) -> Vec<&[u8]> {
+    line.split(u8::is_ascii_whitespace)
+        .filter(|cell| !cell.is_empty())
+        .collect()
+}
+
+fn parse_cell<T: FromStr>(cell: &[u8]) -> Option<T> {
+    std::str::from_utf8(cell).ok()?.parse().ok()
+}
+
+/// The number of an `rs<id>` identifier, as listed in rsid lists and VCFs.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct RsId(pub u64);
+
+/// A position in an [`RsIdPoly`], assigned to an rsid by its line in the list
+/// or explicitly, see [`read_rsid_list`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Index(pub usize);
+
+impl FromStr for RsId {
+    type Err = ();
+
+    /// Parses `rs<id>`.
+    fn from_str(s: &str) -> Result<Self, ()> {
+        s.strip_prefix("rs")
+            .and_then(|id| id.parse().ok())
+            .map(RsId)
+            .ok_or(())
+    }
+}
+
+impl fmt::Display for RsId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rs{}", self.0)
+    }
+}
+
+impl fmt::Display for Index {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Reads a list of `rs<id>` lines, assigning each rsid its position in the list
+/// as index. The `rs` prefix is optional; blank lines are skipped and take no
+/// index, and trailing whitespace, such as a stray `\r`, is ignored.
+///
+/// Lines may instead carry an explicit index, as `rs<id><TAB><index>`, so that
+/// editing the list does not shift the indices of the other rsids. The format
+/// is taken from the first line; mixing both, or repeating an rsid or index in
+/// the explicit format, is an error.
+pub fn read_rsid_list(list: impl Read) -> Result<HashMap<RsId, Index>, Error> {
+    let mut filter = HashMap::new();
+    let mut indices = HashSet::new();
+    let mut explicit = None;
+    let mut position = 0;
+    for (x, line) in BufReader::new(list).lines().enumerate() {
+        let line = line.inspect_err(|_| {
+            error!("Error reading the rsid list");
+        })?;
+        let cells = line.split_whitespace().collect::<Vec<_>>();
+        if cells.is_empty() {
+            continue;
+        }
+        let (rsid, index) = match (cells.as_slice(), *explicit.get_or_insert(cells.len() == 2)) {
+            ([rsid], false) => (rsid, Index(position)),
+            ([rsid, index], true) => match index.parse() {
+                Ok(index) => (rsid, Index(index)),
+                Err(_) => {
+                    error!("Invalid index {} on line {} of the rsid list", index, x + 1);
+                    return Err(Error::Parse { line: x + 1 });
+                }
+            },
+            _ => {
+                error!("Malformed line {} of the rsid list", x + 1);
+                return Err(Error::Parse { line: x + 1 });
+            }
+        };
+        position += 1;
+        let rsid = rsid
+            .strip_prefix("rs")
+            .unwrap_or(rsid)
+            .parse()
+            .map(RsId)
+            .map_err(|_| {
+                error!("Invalid rsid {} on line {} of the rsid list", rsid, x + 1);
+                Error::Parse { line: x + 1 }
+            })?;
+        if filter.insert(rsid, index).is_some() && explicit == Some(true) {
+            error!("Duplicate {} in the rsid list", rsid);
+            return Err(Error::Parse { line: x + 1 });
+        }
+        if !indices.insert(index) {
+            error!("Duplicate index {} in the rsid list", index);
+            return Err(Error::Parse { line: x + 1 });
+        }
+    }
+    Ok(filter)
+}
+
+/// Reads a dbSNP merge table of `rs<old><TAB>rs<new>` lines, mapping rsids
+/// retired by a merge to the rsid they were merged into.
+pub fn read_merge_table(table: impl Read) -> Result<HashMap<RsId, RsId>, Error> {
+    let mut merges = HashMap::new();
+    for (x, line) in BufReader::new(table).lines().enumerate() {
+        let line = line.inspect_err(|_| {
+            error!("Error reading the merge table");
+        })?;
+        let (old, new) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [old, new] => (old.parse::<RsId>(), new.parse::<RsId>()),
+            _ => (Err(()), Err(())),
+        };
+        match (old, new) {
+            (Ok(old), Ok(new)) => merges.insert(old, new),
+            _ => {
+                error!("Malformed line {} of the merge table", x + 1);
+                return Err(Error::Parse { line: x + 1 });
+            }
+        };
+    }
+    Ok(merges)
+}
+
+/// The value of `key` in a `;`-separated INFO column, `None` if it is absent
+/// or a flag.
+fn info_value<'a>(info: &'a [u8], key: &str) -> Option<&'a [u8]> {
+    info.split(|&b| b == b';').find_map(|entry| {
+        let value = entry.strip_prefix(key.as_bytes())?;
+        value.strip_prefix(b"=")
+    })
+}
+
+/// The number of chromosome slots in a [`DnaPoly`] and [`DnaHash`], see
+/// [`chromosome_slot`].
+pub const CHROMOSOMES: usize = 25;
+
+/// The number of a VCF chromosome name, with or without a `chr` prefix: 1 to
+/// 22 for the autosomes, 23 for X, 24 for Y and 25 for MT (or M).
+fn chromosome_to_int(chr: &[u8]) -> Result<usize, ()> {
+    let chr = chr.strip_prefix(b"chr").unwrap_or(chr);
+    match chr {
+        b"X" => Ok(23),
+        b"Y" => Ok(24),
+        b"MT" | b"M" => Ok(25),
+        _ => match parse_cell::<usize>(chr) {
+            Some(n @ 1..=22) => Ok(n),
+            _ => Err(()),
+        },
+    }
+}
+
+/// The slot of a VCF chromosome name in a [`DnaPoly`] and [`DnaHash`]: 0 to 21
+/// for chromosomes 1 to 22, 22 for X, 23 for Y and 24 for MT.
+pub fn chromosome_slot(chr: &[u8]) -> Result<usize, Error> {
+    chromosome_to_int(chr)
+        .map(|n| n - 1)
+        .map_err(|_| Error::UnknownChromosome)
 }
 
 #[derive(PartialEq, Eq, Debug, CanonicalSerialize, CanonicalDeserialize)]
-pub struct DnaHash<E: Pairing>([Commitment<E>; 23]);
+pub struct DnaHash<E: Pairing>([Commitment<E>; CHROMOSOMES]);
 
 #[derive(PartialEq, Eq, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct RsIdHash<E: Pairing>(Commitment<E>);
 
-pub struct DnaPoly<F: From<u8>>([(Vec<usize>, Vec<F>); 23]);
+pub struct DnaPoly<F: From<u8>>([(Vec<usize>, Vec<F>); CHROMOSOMES]);
+
+/// Openings of a [`DnaHash`] at positions on several chromosomes, each tied to
+/// its chromosome so that it is checked against that chromosome's commitment.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregateDnaProof<E: Pairing> {
+    /// `(chromosome, position, value)` of each opening, in request order.
+    openings: Vec<(usize, usize, E::ScalarField)>,
+    proofs: Vec<PointProof<E>>,
+}
 
 #[derive(Debug)]
-pub struct RsIdPoly<F: From<u8>>((Vec<usize>, Vec<F>));
+pub struct RsIdPoly<F>((Vec<usize>, Vec<F>));
 
-impl<F: From<u8>> DnaPoly<F> {
-    #[allow(unused)]
-    pub fn from_file(vcf: impl Read) -> Self {
-        let reader = BufReader::new(vcf);
+/// Genotype values and their quality scores, sharing the same indices.
+#[derive(Debug)]
+pub struct QualifiedRsIdPoly<F> {
+    values: RsIdPoly<F>,
+    quality: RsIdPoly<F>,
+}
+
+/// Commitments to the genotype values and to their quality scores.
+#[derive(PartialEq, Eq, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct QualifiedRsIdHash<E: Pairing> {
+    values: Commitment<E>,
+    quality: Commitment<E>,
+}
 
-        // read one record
-        let mut records: [(Vec<usize>, Vec<F>); 23] = Default::default();
+/// Openings of both the value and the quality commitment at one index.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct QualifiedPointProof<E: Pairing> {
+    values: PointProof<E>,
+    quality: PointProof<E>,
+}
 
-        for line in reader.lines() {
-            let line = line.unwrap();
-            if line.starts_with("##") {
-                continue;
-            }
+/// Genotypes of a cohort: one row per sample, all over the index space of the
+/// same rsid list.
+///
+/// Every row, and the panel itself, is committed under the same public
+/// parameters. That is what makes the commitments comparable across samples
+/// and lets a cell proof tie a sample's value and the panel's rsid to one
+/// index; the parameters must cover the largest index of the list.
+pub struct GenotypeMatrix<F> {
+    filter: HashMap<RsId, Index>,
+    rows: Vec<RsIdPoly<F>>,
+}
+
+/// Commitments to every sample row of a [`GenotypeMatrix`], and to the panel:
+/// the polynomial holding at each index the number of the rsid assigned to it.
+#[derive(PartialEq, Eq, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GenotypeMatrixHash<E: Pairing> {
+    samples: Vec<Commitment<E>>,
+    panel: Commitment<E>,
+}
 
-            let cells = line.split_whitespace().collect::<Vec<_>>();
+/// Openings of a sample row and of the panel at the same index.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct CellProof<E: Pairing> {
+    value: PointProof<E>,
+    rsid: PointProof<E>,
+}
+
+/// Assigns the index in an [`RsIdPoly`] of every rsid to commit to.
+pub trait RsIdFilter {
+    /// The index of `rsid`, `None` if it is not committed.
+    fn index_of(&self, rsid: RsId) -> Option<Index>;
+
+    /// How many rsids the filter selects, the denominator of [`MatchStats`];
+    /// 0 if it is open-ended.
+    fn total(&self) -> usize;
+}
 
-            let chromosome = chromosome_to_int(cells[0].as_bytes());
-            let position = cells[1].parse::<usize>().unwrap();
-            let alternative = base_to_int(cells[4].as_bytes());
+/// An rsid list, as read by [`read_rsid_list`].
+impl RsIdFilter for HashMap<RsId, Index> {
+    fn index_of(&self, rsid: RsId) -> Option<Index> {
+        self.get(&rsid).copied()
+    }
+
+    fn total(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Selects every rsid, at the index `hash(rsid) mod buckets`, so that committer
+/// and verifier need no shared ordered list.
+///
+/// Distinct rsids may share a bucket. Colliding values are summed into it, like
+/// repeated indices anywhere in an [`RsIdPoly`], so an opening at a bucket is an
+/// opening of that sum: it only proves a value for an rsid when no other matched
+/// rsid landed in the same bucket, which the verifier cannot check from the
+/// commitment. Keep `buckets` much larger than the number of rsids committed.
+/// [`MatchStats::matched`] counts buckets, so it undercounts on collisions.
+#[derive(Clone, Copy, Debug)]
+pub struct HashBucketFilter {
+    buckets: usize,
+    hash: fn(RsId) -> u64,
+}
+
+/// The first 8 bytes of the SHA-256 of the little-endian rsid number.
+fn sha256_rsid(rsid: RsId) -> u64 {
+    let digest = Sha256::digest(rsid.0.to_le_bytes());
+    u64::from_le_bytes(digest[..8].try_into().unwrap())
+}
+
+impl HashBucketFilter {
+    /// Buckets by SHA-256; `buckets` should not exceed the SRS size.
+    pub fn new(buckets: usize) -> Self {
+        Self::with_hash(buckets, sha256_rsid)
+    }
+
+    pub fn with_hash(buckets: usize, hash: fn(RsId) -> u64) -> Self {
+        assert!(buckets > 0, "a bucket filter needs at least one bucket");
+        Self { buckets, hash }
+    }
+}
+
+impl RsIdFilter for HashBucketFilter {
+    fn index_of(&self, rsid: RsId) -> Option<Index> {
+        Some(Index(((self.hash)(rsid) % self.buckets as u64) as usize))
+    }
 
+    fn total(&self) -> usize {
+        0
+    }
+}
+
+/// Reads a VCF into an [`RsIdPoly`], mapping each ALT allele to a field element
+/// with `encoding`, or committing to allele dosages, see [`Self::dosage`].
+pub struct RsIdPolyBuilder<F, L = HashMap<RsId, Index>> {
+    filter: L,
+    encoding: fn(&[u8]) -> F,
+    values: Values<F>,
+    multi_allelic: MultiAllelic,
+    pad_to: Option<usize>,
+    merges: HashMap<RsId, RsId>,
+}
+
+/// What a multi-allelic record commits to when its genotype calls no ALT
+/// allele: none at all, a missing one (`./.`) or a homozygous reference `0/0`;
+/// see [`RsIdPolyBuilder::multi_allelic`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MultiAllelic {
+    /// The first ALT allele, logging a warning.
+    #[default]
+    First,
+    /// Nothing: the record is skipped like an absent one.
+    Skip,
+}
+
+/// What an [`RsIdPolyBuilder`] commits to for a matched record.
+enum Values<F> {
+    /// The ALT allele, through the builder's encoding.
+    Alt,
+    /// The REF and ALT alleles, see [`RsIdPolyBuilder::allele_hash`].
+    Allele(fn(&[u8], &[u8]) -> F),
+    /// The number of ALT alleles called, and what a missing call commits to,
+    /// `None` to skip the record.
+    Dosage(fn(u8) -> F, Option<u8>),
+}
+
+/// How many entries of the rsid filter were found in a VCF.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MatchStats {
+    pub matched: usize,
+    pub total: usize,
+    /// Matches at or past the length set by [`RsIdPolyBuilder::pad_to`], which
+    /// are not committed.
+    pub dropped: usize,
+}
+
+/// Which rsids a VCF covered, as found by
+/// [`RsIdPolyBuilder::from_file_with_report`]. Each list is sorted.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct MatchReport {
+    /// Rsids of the list with a value in the VCF.
+    pub matched: Vec<RsId>,
+    /// Rsids of the list absent from the VCF, committed as 0.
+    pub absent: Vec<RsId>,
+    /// Rsids of the VCF that are not committed: not in the list, dropped by
+    /// [`RsIdPolyBuilder::pad_to`], or skipped for a missing genotype.
+    pub unlisted: Vec<RsId>,
+}
+
+impl MatchStats {
+    pub fn rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.matched as f64 / self.total as f64
+        }
+    }
+}
+
+/// Number of VCF lines each task parses in [`RsIdPolyBuilder::par_from_file`].
+const PARSE_CHUNK_SIZE: usize = 1 << 14;
+
+/// Checks that `max_index` is within `pp`, before committing would panic on it.
+fn check_fits<E: Pairing>(
+    pp: &PublicParameters<E>,
+    max_index: Option<&usize>,
+) -> Result<(), Error> {
+    match max_index {
+        Some(&index) if index > pp.max_degree() => {
+            error!(
+                "Index {} is past the maximum degree {} of the public parameters; \
+                 generate them with a larger -D",
+                index,
+                pp.max_degree()
+            );
+            Err(Error::IndexNotFound(index))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn base_encoding<F: From<u8>>(alt: &[u8]) -> F {
+    base_to_int(alt).into()
+}
+
+fn distinct_base_encoding<F: From<u8>>(alt: &[u8]) -> F {
+    distinct_base_to_int(alt).into()
+}
+
+impl<F: From<u8>> DnaPoly<F> {
+    /// Records with an unknown chromosome are skipped with a warning.
+    #[allow(unused)]
+    pub fn from_file(vcf: impl Read) -> Self {
+        let mut records: [(Vec<usize>, Vec<F>); CHROMOSOMES] = Default::default();
+        for (chromosome, position, value) in Self::records(vcf) {
             records[chromosome].0.push(position);
-            records[chromosome].1.push(alternative.into())
+            records[chromosome].1.push(value);
         }
-        records.sort_by(|(i, a), (j, b)| i.partial_cmp(j).unwrap());
         Self(records)
     }
+
+    /// The records of `vcf` as `(slot, position, value)`, parsed one line at a
+    /// time as they are consumed; [`Self::from_file`] collects them. Lines with
+    /// fewer than five columns are skipped, and records with a malformed POS
+    /// are skipped with a warning.
+    pub fn records(vcf: impl Read) -> impl Iterator<Item = (usize, usize, F)> {
+        raw_lines(vcf).filter_map(|line| {
+            if line.starts_with(b"#") {
+                return None;
+            }
+
+            let cells = split_cells(&line);
+            if cells.len() < 5 {
+                return None;
+            }
+
+            let Ok(chromosome) = chromosome_slot(cells[0]) else {
+                warn!(
+                    "Skipping a record on unknown chromosome {}",
+                    String::from_utf8_lossy(cells[0])
+                );
+                return None;
+            };
+            let Some(position) = parse_cell::<usize>(cells[1]) else {
+                warn!(
+                    "Skipping a record with malformed position {}",
+                    String::from_utf8_lossy(cells[1])
+                );
+                return None;
+            };
+            let alternative = base_to_int(cells[4]);
+            Some((chromosome, position, alternative.into()))
+        })
+    }
 }
 
 impl<E: Pairing> DnaHash<E> {
     #[allow(unused)]
-    pub fn new(pp: &PublicParameters<E>, vcf: &DnaPoly<E::ScalarField>) -> Self {
-        let mut commitments = [Commitment::default(); 23];
-        for i in 0..23 {
-            commitments[i] = pp.commit_sparse(&vcf.0[i]);
+    /// Fails, instead of panicking, if a position is past the parameters.
+    pub fn new(pp: &PublicParameters<E>, vcf: &DnaPoly<E::ScalarField>) -> Result<Self, Error> {
+        check_fits(pp, vcf.0.iter().flat_map(|(positions, _)| positions).max())?;
+        let mut commitments = [Commitment::default(); CHROMOSOMES];
+        commitments
+            .par_iter_mut()
+            .zip(vcf.0.par_iter())
+            .try_for_each(|(commitment, chromosome)| {
+                *commitment = pp.commit_sparse(chromosome).map_err(Error::IndexNotFound)?;
+                Ok::<(), Error>(())
+            })?;
+        Ok(Self(commitments))
+    }
+
+    /// Like [`Self::new`] over [`DnaPoly::from_file`], committing to each
+    /// chromosome `window` records at a time, so that memory is bounded by one
+    /// window per chromosome rather than by the size of the VCF.
+    pub fn commit_stream(
+        pp: &PublicParameters<E>,
+        vcf: impl Read,
+        window: usize,
+    ) -> Result<Self, Error> {
+        let window = window.max(1);
+        let mut commitments = [Commitment::default(); CHROMOSOMES];
+        let mut windows: [(Vec<usize>, Vec<E::ScalarField>); CHROMOSOMES] = Default::default();
+        let flush = |commitment: &mut Commitment<E>,
+                     records: &mut (Vec<usize>, Vec<E::ScalarField>)|
+         -> Result<(), Error> {
+            check_fits(pp, records.0.iter().max())?;
+            *commitment += pp.commit_sparse(records).map_err(Error::IndexNotFound)?;
+            records.0.clear();
+            records.1.clear();
+            Ok(())
+        };
+
+        for (chromosome, position, value) in DnaPoly::<E::ScalarField>::records(vcf) {
+            let records = &mut windows[chromosome];
+            records.0.push(position);
+            records.1.push(value);
+            if records.0.len() == window {
+                flush(&mut commitments[chromosome], records)?;
+            }
         }
-        Self(commitments)
+        for (commitment, records) in commitments.iter_mut().zip(&mut windows) {
+            flush(commitment, records)?;
+        }
+        Ok(Self(commitments))
     }
 
     #[allow(unused)]
@@ -75,59 +650,1909 @@ impl<E: Pairing> DnaHash<E> {
         pp: &PublicParameters<E>,
         vcf: &DnaPoly<E::ScalarField>,
         index: (usize, usize),
-    ) -> Result<PointProof<E>, ()> {
-        PointProof::new_sparse(pp, &vcf.0[index.0], index.1)
+    ) -> Result<PointProof<E>, Error> {
+        Ok(PointProof::new_sparse(pp, &vcf.0[index.0], index.1)?)
+    }
+
+    /// Opens every `(chromosome, position)` of `requests` into one
+    /// [`AggregateDnaProof`], disclosing the values there.
+    pub fn prove_aggregate(
+        pp: &PublicParameters<E>,
+        vcf: &DnaPoly<E::ScalarField>,
+        requests: &[(usize, usize)],
+    ) -> Result<AggregateDnaProof<E>, Error> {
+        let proofs = Self::prove_many(pp, vcf, requests)?;
+        let openings = requests
+            .iter()
+            .map(|&(chromosome, position)| {
+                let (positions, values) = &vcf.0[chromosome];
+                let value = positions
+                    .iter()
+                    .zip(values)
+                    .filter(|(&i, _)| i == position)
+                    .map(|(_, &x)| x)
+                    .sum();
+                (chromosome, position, value)
+            })
+            .collect();
+        Ok(AggregateDnaProof { openings, proofs })
+    }
+
+    /// The commitment to the chromosome in slot `chr`, see [`chromosome_slot`].
+    pub fn chromosome(&self, chr: usize) -> Option<&Commitment<E>> {
+        self.0.get(chr)
+    }
+
+    /// The per-chromosome commitments, with their numbers.
+    pub fn chromosomes(&self) -> impl Iterator<Item = (usize, &Commitment<E>)> {
+        self.0.iter().enumerate()
+    }
+
+    /// Like [`Self::prove`] for each of `indices`, in order. Requests are grouped
+    /// by chromosome so that each chromosome is committed to only once, see
+    /// [`PointProof::new_sparse_many`].
+    pub fn prove_many(
+        pp: &PublicParameters<E>,
+        vcf: &DnaPoly<E::ScalarField>,
+        indices: &[(usize, usize)],
+    ) -> Result<Vec<PointProof<E>>, Error> {
+        let mut by_chromosome = vec![Vec::new(); vcf.0.len()];
+        for &(chromosome, position) in indices {
+            by_chromosome
+                .get_mut(chromosome)
+                .ok_or(Error::IndexNotFound(chromosome))?
+                .push(position);
+        }
+        let proofs = vcf
+            .0
+            .par_iter()
+            .zip(by_chromosome.par_iter())
+            .map(|(chromosome, positions)| {
+                PointProof::new_sparse_many(pp, chromosome, positions)
+                    .map(|proofs| positions.iter().copied().zip(proofs).collect())
+            })
+            .collect::<Result<Vec<HashMap<_, _>>, _>>()?;
+        Ok(indices
+            .iter()
+            .map(|(chromosome, position)| proofs[*chromosome][position].clone())
+            .collect())
+    }
+}
+
+impl<E: Pairing> AggregateDnaProof<E> {
+    /// The disclosed `(chromosome, position, value)` triples.
+    pub fn openings(&self) -> &[(usize, usize, E::ScalarField)] {
+        &self.openings
+    }
+
+    /// Checks every opening against the commitment of its chromosome in `hash`,
+    /// with a single [`PointProof::batch_verify_across`]. An `Err` if an opening
+    /// names a chromosome or position outside `hash` and `pp`.
+    pub fn verify(
+        &self,
+        pp: &PublicParameters<E>,
+        hash: &DnaHash<E>,
+        rng: &mut impl RngCore,
+    ) -> Result<VerifyOutcome, Error> {
+        if self.openings.len() != self.proofs.len() {
+            return Err(CommitmentError::Malformed.into());
+        }
+        let openings = self
+            .openings
+            .iter()
+            .zip(&self.proofs)
+            .map(|(&(chromosome, position, value), proof)| {
+                Ok((
+                    hash.chromosome(chromosome)
+                        .ok_or(Error::IndexNotFound(chromosome))?,
+                    position,
+                    value,
+                    proof,
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(PointProof::batch_verify_across(pp, &openings, rng)?)
     }
 }
 
 impl<E: Pairing> RsIdHash<E> {
-    pub fn new(pp: &PublicParameters<E>, rsid_poly: &RsIdPoly<E::ScalarField>) -> Self {
-        Self(pp.commit_sparse(&rsid_poly.0))
+    /// Fails, instead of panicking, if an index is past the parameters: the
+    /// rsid list is too long for them.
+    pub fn new(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+    ) -> Result<Self, Error> {
+        check_fits(pp, rsid_poly.0 .0.iter().max())?;
+        pp.commit_sparse(&rsid_poly.0)
+            .map(Self)
+            .map_err(Error::IndexNotFound)
+    }
+
+    /// Like [`Self::new`], with the commitment offset by the application `tag`
+    /// (see [`Commitment::tagged`]). Proofs are made as usual with
+    /// [`Self::prove`], and verified against [`Self::untagged`] with the same tag.
+    pub fn new_tagged(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+        tag: &[u8],
+    ) -> Result<Self, Error> {
+        Ok(Self(Self::new(pp, rsid_poly)?.0.tagged(pp, tag)))
+    }
+
+    /// Like [`Self::new`], hiding the values, see
+    /// [`PublicParameters::commit_sparse_hiding`]. The blinding must be kept
+    /// secret and is needed by [`Self::prove_hiding`].
+    pub fn new_hiding(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+        csrng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Self, Blinding<E>), Error> {
+        check_fits(pp, rsid_poly.0 .0.iter().max())?;
+        let (commitment, blinding) = pp
+            .commit_sparse_hiding(&rsid_poly.0, csrng)
+            .map_err(Error::IndexNotFound)?;
+        Ok((Self(commitment), blinding))
+    }
+
+    /// The hash [`Self::new_hiding`] returned together with `blinding`.
+    pub fn with_blinding(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+        blinding: &Blinding<E>,
+    ) -> Result<Self, Error> {
+        Ok(Self(
+            Self::new(pp, rsid_poly)?.0.with_blinding(pp, blinding),
+        ))
+    }
+
+    /// Changes the value committed at `index`, e.g. after a re-call corrects a
+    /// variant, without recommitting; see [`Commitment::update`].
+    pub fn update(
+        &mut self,
+        pp: &PublicParameters<E>,
+        index: Index,
+        old_value: E::ScalarField,
+        new_value: E::ScalarField,
+    ) -> Result<(), Error> {
+        Ok(self.0.update(pp, index.0, old_value, new_value)?)
+    }
+
+    /// The commitment proofs open, for a hash made with [`Self::new_tagged`].
+    pub fn untagged(&self, pp: &PublicParameters<E>, tag: &[u8]) -> Commitment<E> {
+        self.0.untagged(pp, tag)
+    }
+
+    /// The underlying commitment, for verifying proofs against this hash.
+    pub fn commitment(&self) -> Commitment<E> {
+        self.0
     }
 
     pub fn prove(
         pp: &PublicParameters<E>,
         rsid_poly: &RsIdPoly<E::ScalarField>,
-        rsid: usize,
-    ) -> Result<PointProof<E>, ()> {
-        PointProof::new_sparse(pp, &rsid_poly.0, rsid)
+        index: Index,
+    ) -> Result<PointProof<E>, Error> {
+        Ok(PointProof::new_sparse(pp, &rsid_poly.0, index.0)?)
+    }
+
+    /// Like [`Self::prove`], also checking the opening against `hash`, so that a
+    /// hash that is not the commitment to `rsid_poly` is caught rather than
+    /// answered with a proof that does not verify against it.
+    pub fn prove_checked(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+        hash: &RsIdHash<E>,
+        index: Index,
+    ) -> Result<PointProof<E>, Error> {
+        let proof = Self::prove(pp, rsid_poly, index)?;
+        proof
+            .verify(pp, &hash.0, index.0, rsid_poly.value_at(index))?
+            .check()
+            .inspect_err(|_| error!("The hash is not the commitment to the polynomial"))?;
+        Ok(proof)
+    }
+
+    /// Proves that `index` was not populated: a [`PointProof`] that verifies
+    /// only with the value zero, as the sparse commitment omits absent indices.
+    ///
+    /// A zero opening cannot tell an absent index from one that carried the
+    /// value 0, which the base encoding uses for the reference allele and for
+    /// unknown bases. This refuses to prove a populated index, even one holding
+    /// 0, but a verifier learns only "absent or zero" unless the encoding in use
+    /// never produces 0 (the dosage of [`RsIdPolyBuilder::dosage`] does, too).
+    pub fn prove_absent(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+        index: Index,
+    ) -> Result<PointProof<E>, Error> {
+        if index.0 > pp.max_degree() {
+            error!("Index {} is past the parameters", index.0);
+            return Err(Error::IndexNotFound(index.0));
+        }
+        if rsid_poly.0 .0.contains(&index.0) {
+            error!("Index {} is populated", index.0);
+            return Err(Error::IndexPopulated(index.0));
+        }
+        Self::prove(pp, rsid_poly, index)
+    }
+
+    /// Like [`Self::prove`], for a hash made with [`Self::new_hiding`].
+    pub fn prove_hiding(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+        index: Index,
+        blinding: &Blinding<E>,
+        csrng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<PointProof<E>, Error> {
+        Ok(PointProof::new_sparse_hiding(
+            pp,
+            &rsid_poly.0,
+            index.0,
+            blinding,
+            csrng,
+        )?)
+    }
+
+    /// Like [`Self::prove`] for each of `indices`, in order, sharing the work
+    /// through [`PointProof::new_sparse_many`].
+    pub fn prove_many(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+        indices: &[Index],
+    ) -> Result<Vec<PointProof<E>>, Error> {
+        let indices = indices.iter().map(|index| index.0).collect::<Vec<_>>();
+        Ok(PointProof::new_sparse_many(pp, &rsid_poly.0, &indices)?)
+    }
+
+    /// [`Self::new`] and [`Self::prove_many`] in one pass, for issuing many
+    /// proofs from one genome: the commitment falls out of the MSMs the proofs
+    /// share, through [`PointProof::commit_and_open`].
+    pub fn commit_and_open(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+        indices: &[Index],
+    ) -> Result<(Self, Vec<PointProof<E>>), Error> {
+        let indices = indices.iter().map(|index| index.0).collect::<Vec<_>>();
+        check_fits(pp, rsid_poly.0 .0.iter().chain(&indices).max())?;
+        let (commitment, proofs) = PointProof::commit_and_open(pp, &rsid_poly.0, &indices)
+            .expect("an RsIdPoly has a value per index");
+        Ok((Self(commitment), proofs))
+    }
+
+    /// Proves that `index_a` and `index_b` hold the same value without
+    /// disclosing it, for a hash made with [`Self::new_hiding`]; see
+    /// [`SameValueProof`].
+    pub fn prove_same_value(
+        pp: &PublicParameters<E>,
+        rsid_poly: &RsIdPoly<E::ScalarField>,
+        index_a: Index,
+        index_b: Index,
+        blinding: &Blinding<E>,
+        csrng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<SameValueProof<E>, Error> {
+        Ok(SameValueProof::new_sparse(
+            pp,
+            &rsid_poly.0,
+            index_a.0,
+            index_b.0,
+            blinding,
+            csrng,
+        )?)
+    }
+
+    /// Proves that `merged`, e.g. the calls of two callers combined, is the union
+    /// of `src_a` and `src_b`; see [`MergeProof`] for how overlaps are handled.
+    pub fn prove_merge(
+        pp: &PublicParameters<E>,
+        src_a: &RsIdPoly<E::ScalarField>,
+        src_b: &RsIdPoly<E::ScalarField>,
+        merged: &RsIdPoly<E::ScalarField>,
+    ) -> Result<MergeProof<E>, Error> {
+        Ok(MergeProof::new_sparse(pp, &src_a.0, &src_b.0, &merged.0)?)
     }
 }
 
-impl<F: From<u8>> RsIdPoly<F> {
-    pub fn from_file(vcf: impl Read, filter: HashMap<usize, usize>) -> Self {
-        let reader = BufReader::new(vcf);
-        let mut records: (Vec<usize>, Vec<F>) = Default::default();
+impl<E: Pairing> QualifiedRsIdHash<E> {
+    pub fn new(
+        pp: &PublicParameters<E>,
+        poly: &QualifiedRsIdPoly<E::ScalarField>,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            values: pp
+                .commit_sparse(&poly.values.0)
+                .map_err(Error::IndexNotFound)?,
+            quality: pp
+                .commit_sparse(&poly.quality.0)
+                .map_err(Error::IndexNotFound)?,
+        })
+    }
 
-        for line in reader.lines() {
-            let line = line.unwrap();
-            if line.starts_with("##") {
-                continue;
+    pub fn prove(
+        pp: &PublicParameters<E>,
+        poly: &QualifiedRsIdPoly<E::ScalarField>,
+        index: Index,
+    ) -> Result<QualifiedPointProof<E>, Error> {
+        Ok(QualifiedPointProof {
+            values: PointProof::new_sparse(pp, &poly.values.0, index.0)?,
+            quality: PointProof::new_sparse(pp, &poly.quality.0, index.0)?,
+        })
+    }
+}
+
+impl<E: Pairing> QualifiedPointProof<E> {
+    /// Verifies both openings and that the opened quality is at least `threshold`.
+    pub fn verify(
+        &self,
+        pp: &PublicParameters<E>,
+        hash: &QualifiedRsIdHash<E>,
+        index: Index,
+        value: E::ScalarField,
+        quality: u64,
+        threshold: u64,
+    ) -> Result<VerifyOutcome, Error> {
+        if quality < threshold {
+            error!("Quality {} is below the threshold {}", quality, threshold);
+            return Ok(VerifyOutcome::Invalid);
+        }
+        let values = self.values.verify(pp, &hash.values, index.0, value)?;
+        let quality = self
+            .quality
+            .verify(pp, &hash.quality, index.0, quality.into())?;
+        Ok((values.is_valid() && quality.is_valid()).into())
+    }
+}
+
+impl<F: From<u8>, L: RsIdFilter> RsIdPolyBuilder<F, L> {
+    pub fn new(filter: L) -> Self {
+        Self::with_encoding(filter, base_encoding::<F>)
+    }
+}
+
+impl<F, L: RsIdFilter> RsIdPolyBuilder<F, L> {
+    pub fn with_encoding(filter: L, encoding: fn(&[u8]) -> F) -> Self {
+        Self {
+            filter,
+            encoding,
+            values: Values::Alt,
+            multi_allelic: MultiAllelic::First,
+            pad_to: None,
+            merges: HashMap::new(),
+        }
+    }
+
+    /// Sets how a multi-allelic ALT field (`A,G`) is resolved when the genotype
+    /// calls no ALT allele. When it does, the called ALT allele is encoded, the
+    /// lowest-numbered one for a heterozygous `1/2`. A `0/0` call thus commits
+    /// the first ALT allele by default, as a biallelic record does whatever its
+    /// genotype; a call past the ALT alleles skips the record with a warning.
+    pub fn multi_allelic(mut self, multi_allelic: MultiAllelic) -> Self {
+        self.multi_allelic = multi_allelic;
+        self
+    }
+
+    /// Commits to the number of ALT alleles called in the first sample's `GT`,
+    /// 0 to 2 for a diploid call, phased (`0|1`) or not (`0/1`), instead of to
+    /// the ALT allele. A missing call (`./.`), or a record without `GT`,
+    /// commits to `missing`, or is skipped like an absent record if `None`.
+    pub fn dosage(mut self, missing: Option<u8>) -> Self
+    where
+        F: From<u8>,
+    {
+        self.values = Values::Dosage(F::from, missing);
+        self
+    }
+
+    /// Commits to REF and ALT together, as [`ALLELE_HASH_ENCODING`] with
+    /// [`allele_value`], so that indels and structural variants are committed
+    /// instead of encoding as 0 like the reference.
+    pub fn allele_hash(mut self) -> Self
+    where
+        F: PrimeField,
+    {
+        self.values = Values::Allele(allele_value::<F>);
+        self
+    }
+
+    /// Fixes the index space of the polynomial to `0..len`, independently of how
+    /// many rsids match. Absent indices commit as 0; matches at or past `len` are
+    /// dropped and counted in [`MatchStats::dropped`].
+    pub fn pad_to(mut self, len: Option<usize>) -> Self {
+        self.pad_to = len;
+        self
+    }
+
+    /// Redirects VCF rsids that are not in the filter through `merges`, as read
+    /// by [`read_merge_table`], so that a VCF on an older dbSNP release than the
+    /// list still matches. Only one merge is followed.
+    pub fn merge_table(mut self, merges: HashMap<RsId, RsId>) -> Self {
+        self.merges = merges;
+        self
+    }
+
+    pub fn from_file(&self, vcf: impl Read) -> (RsIdPoly<F>, MatchStats) {
+        let mut records: (Vec<usize>, Vec<F>) = (Vec::new(), Vec::new());
+        let dropped = self.for_each_match(vcf, |index, value, _| {
+            records.0.push(index);
+            records.1.push(value);
+        });
+        let stats = self.match_stats(&records.0, dropped);
+        (RsIdPoly(records), stats)
+    }
+
+    /// The matches of `vcf` as `(index, value)`, parsed one line at a time as
+    /// they are consumed, without those dropped by [`Self::pad_to`];
+    /// [`Self::commit_stream`] commits to them in windows.
+    pub fn records<'a>(&'a self, vcf: impl Read + 'a) -> impl Iterator<Item = (usize, F)> + 'a {
+        raw_lines(vcf).filter_map(|line| {
+            let (index, value, _) = self.match_line(&line)?;
+            if self.padded_out(index) {
+                warn!("Dropping index {}: outside the padded length", index);
+                return None;
             }
+            Some((index, value))
+        })
+    }
 
-            let cells = line.split_whitespace().collect::<Vec<_>>();
+    /// Like [`Self::from_file`], parsing chunks of lines in parallel.
+    ///
+    /// The VCF is read in batches of one chunk per thread, so that memory stays
+    /// bounded by the batch rather than by the size of the VCF. Each chunk yields
+    /// a partial polynomial in file order and the partials are concatenated in
+    /// chunk order, so the result is identical to the serial path.
+    pub fn par_from_file(&self, vcf: impl Read) -> (RsIdPoly<F>, MatchStats)
+    where
+        F: Send,
+        L: Sync,
+    {
+        self.par_from_reader(vcf, PARSE_CHUNK_SIZE, rayon::current_num_threads())
+    }
 
-            if !cells[2].starts_with("rs") {
-                continue;
+    fn par_from_reader(
+        &self,
+        vcf: impl Read,
+        chunk_size: usize,
+        chunks_per_batch: usize,
+    ) -> (RsIdPoly<F>, MatchStats)
+    where
+        F: Send,
+        L: Sync,
+    {
+        let batch_size = chunk_size * chunks_per_batch.max(1);
+        let mut lines = raw_lines(vcf);
+        let mut records: (Vec<usize>, Vec<F>) = (Vec::new(), Vec::new());
+        let mut dropped = 0;
+        loop {
+            let batch = lines.by_ref().take(batch_size).collect::<Vec<_>>();
+            if batch.is_empty() {
+                break;
+            }
+            let partials = batch
+                .par_chunks(chunk_size)
+                .map(|chunk| {
+                    let mut partial: (Vec<usize>, Vec<F>) = (Vec::new(), Vec::new());
+                    let mut dropped = 0;
+                    for (index, value, _) in chunk.iter().filter_map(|line| self.match_line(line)) {
+                        if self.padded_out(index) {
+                            dropped += 1;
+                            continue;
+                        }
+                        partial.0.push(index);
+                        partial.1.push(value);
+                    }
+                    (partial, dropped)
+                })
+                .collect::<Vec<_>>();
+            for ((indices, values), partial_dropped) in partials {
+                records.0.extend(indices);
+                records.1.extend(values);
+                dropped += partial_dropped;
+            }
+        }
+
+        let stats = self.match_stats(&records.0, dropped);
+        (RsIdPoly(records), stats)
+    }
+
+    /// Like [`Self::from_file`], additionally reading the `QUAL` column (rounded,
+    /// `.` as 0) into a second polynomial over the same indices.
+    pub fn qualified_from_file(&self, vcf: impl Read) -> (QualifiedRsIdPoly<F>, MatchStats)
+    where
+        F: From<u64>,
+    {
+        let mut values: (Vec<usize>, Vec<F>) = (Vec::new(), Vec::new());
+        let mut quality: (Vec<usize>, Vec<F>) = (Vec::new(), Vec::new());
+        let dropped = self.for_each_match(vcf, |index, value, cells| {
+            let qual = cells
+                .get(5)
+                .and_then(|q| parse_cell::<f64>(q))
+                .map_or(0, |q| q.round() as u64);
+            values.0.push(index);
+            values.1.push(value);
+            quality.0.push(index);
+            quality.1.push(qual.into());
+        });
+
+        let stats = self.match_stats(&values.0, dropped);
+        let poly = QualifiedRsIdPoly {
+            values: RsIdPoly(values),
+            quality: RsIdPoly(quality),
+        };
+        (poly, stats)
+    }
+
+    /// Commits to the polynomial [`Self::from_file`] would read, parsing and
+    /// committing `window` matches at a time so that memory stays bounded by
+    /// the window and the rsid list rather than by the size of the VCF.
+    pub fn commit_stream<E: Pairing<ScalarField = F>>(
+        &self,
+        pp: &PublicParameters<E>,
+        vcf: impl Read,
+        window: usize,
+    ) -> Result<(Commitment<E>, MatchStats), Error> {
+        let window = window.max(1);
+        let mut matched = HashSet::new();
+        let mut dropped = 0;
+        // the records borrow `dropped` until the windows are committed
+        let commitment = {
+            let mut records = raw_lines(vcf).filter_map(|line| {
+                let (index, value, _) = self.match_line(&line)?;
+                if self.padded_out(index) {
+                    dropped += 1;
+                    return None;
+                }
+                Some((index, value))
+            });
+            let windows = std::iter::from_fn(|| {
+                let records: (Vec<usize>, Vec<F>) = records
+                    .by_ref()
+                    .take(window)
+                    .inspect(|&(index, _)| {
+                        matched.insert(index);
+                    })
+                    .unzip();
+                (!records.0.is_empty()).then_some(records)
+            });
+            pp.commit_stream(windows).map_err(Error::IndexNotFound)?
+        };
+
+        Ok((commitment, self.stats(matched.len(), dropped)))
+    }
+
+    /// Like [`Self::from_file`], additionally extracting the INFO key `key` (e.g.
+    /// `AF`) of every match into a vector parallel to the polynomial's values.
+    ///
+    /// Fractional values are fixed point, scaled by `scale` and rounded; for
+    /// multi-allelic records only the first value is kept. Records where the
+    /// key is missing or does not parse as a non-negative number get `fallback`.
+    pub fn info_from_file(
+        &self,
+        vcf: impl Read,
+        key: &str,
+        scale: u64,
+        fallback: F,
+    ) -> (RsIdPoly<F>, Vec<F>, MatchStats)
+    where
+        F: From<u64> + Copy,
+    {
+        let mut records: (Vec<usize>, Vec<F>) = (Vec::new(), Vec::new());
+        let mut info = Vec::new();
+        let dropped = self.for_each_match(vcf, |index, value, cells| {
+            records.0.push(index);
+            records.1.push(value);
+            let value = cells
+                .get(7)
+                .and_then(|field| info_value(field, key))
+                .and_then(|value| parse_cell::<f64>(value.split(|&b| b == b',').next()?))
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .map_or(fallback, |value| {
+                    ((value * scale as f64).round() as u64).into()
+                });
+            info.push(value);
+        });
+
+        let stats = self.match_stats(&records.0, dropped);
+        (RsIdPoly(records), info, stats)
+    }
+
+    /// Calls `f` on every match of `vcf` within the padded length, returning
+    /// how many were dropped past it.
+    fn for_each_match(&self, vcf: impl Read, mut f: impl FnMut(usize, F, &[&[u8]])) -> usize {
+        let mut dropped = 0;
+        for line in raw_lines(vcf) {
+            if let Some((index, value, cells)) = self.match_line(&line) {
+                if self.padded_out(index) {
+                    dropped += 1;
+                } else {
+                    f(index, value, &cells);
+                }
             }
-            let rsid = cells[2][2..].parse::<usize>().unwrap();
-            let alternative = base_to_int(cells[4].as_bytes());
+        }
+        dropped
+    }
+
+    /// Whether `index` is at or past the length set by [`Self::pad_to`].
+    fn padded_out(&self, index: usize) -> bool {
+        self.pad_to.is_some_and(|len| index >= len)
+    }
+
+    /// Splits a VCF line and returns its filter index and value if its rsid is in
+    /// the list, and the record is not skipped. Indices past [`Self::pad_to`] are
+    /// returned too, for the caller to count.
+    fn match_line<'a>(&self, line: &'a [u8]) -> Option<(usize, F, Vec<&'a [u8]>)> {
+        if line.starts_with(b"##") {
+            return None;
+        }
+
+        let cells = split_cells(line);
+        if cells.len() < 5 {
+            return None;
+        }
+
+        // gVCF reference blocks have no rsid and are skipped here
+        let rsid = parse_cell::<RsId>(cells[2])?;
+
+        let index = self
+            .filter
+            .index_of(rsid)
+            .or_else(|| self.filter.index_of(*self.merges.get(&rsid)?))?;
+        let value = self.value(&cells)?;
+        Some((index.0, value, cells))
+    }
+
+    /// The ALT allele a record commits to, resolving a multi-allelic ALT field
+    /// as set by [`Self::multi_allelic`]; `None` if the record is skipped.
+    fn called_alt<'a>(&self, cells: &[&'a [u8]]) -> Option<&'a [u8]> {
+        let alt = strip_non_ref(cells[4]);
+        if !alt.contains(&b',') {
+            return Some(alt);
+        }
+        let alts = alt.split(|&b| b == b',').collect::<Vec<_>>();
+        let alleles = genotype(cells);
+        if let Some(allele) = alleles
+            .iter()
+            .flatten()
+            .find(|&&allele| allele > alts.len())
+        {
+            warn!(
+                "Skipping multi-allelic {}: allele {} called, but there are {} ALT alleles",
+                String::from_utf8_lossy(cells[2]),
+                allele,
+                alts.len()
+            );
+            return None;
+        }
+        let called = alleles
+            .and_then(|alleles| alleles.into_iter().filter(|&allele| allele != 0).min())
+            .map(|allele| alts[allele - 1]);
+        match (called, self.multi_allelic) {
+            (Some(allele), _) => Some(allele),
+            (None, MultiAllelic::First) => {
+                warn!(
+                    "Committing the first ALT allele of multi-allelic {}: no ALT allele called",
+                    String::from_utf8_lossy(cells[2])
+                );
+                Some(alts[0])
+            }
+            (None, MultiAllelic::Skip) => None,
+        }
+    }
+
+    /// The value a record commits to, `None` if it is skipped.
+    fn value(&self, cells: &[&[u8]]) -> Option<F> {
+        match self.values {
+            Values::Alt => self.called_alt(cells).map(self.encoding),
+            Values::Allele(encoding) => self.called_alt(cells).map(|alt| encoding(cells[3], alt)),
+            Values::Dosage(from_dosage, missing) => match genotype(cells) {
+                Some(alleles) => {
+                    let dosage = alleles.iter().filter(|&&allele| allele != 0).count();
+                    Some(from_dosage(u8::try_from(dosage).unwrap_or(u8::MAX)))
+                }
+                None => missing.map(from_dosage),
+            },
+        }
+    }
+
+    fn match_stats(&self, indices: &[usize], dropped: usize) -> MatchStats {
+        self.stats(indices.iter().collect::<HashSet<_>>().len(), dropped)
+    }
+
+    fn stats(&self, matched: usize, dropped: usize) -> MatchStats {
+        if dropped > 0 {
+            warn!(
+                "Dropped {} matches outside the padded length {}",
+                dropped,
+                self.pad_to.unwrap_or_default()
+            );
+        }
+        MatchStats {
+            matched,
+            total: self.filter.total(),
+            dropped,
+        }
+    }
+}
 
-            match filter.get(&rsid) {
-                Some(&index) => {
+impl<F> RsIdPolyBuilder<F, HashMap<RsId, Index>> {
+    /// Like [`Self::from_file`], also reporting which rsids of the list were
+    /// matched or absent, and which rsids of the VCF were left out.
+    pub fn from_file_with_report(&self, vcf: impl Read) -> (RsIdPoly<F>, MatchStats, MatchReport) {
+        let mut records: (Vec<usize>, Vec<F>) = (Vec::new(), Vec::new());
+        let mut unlisted = HashSet::new();
+        let mut dropped = 0;
+        for line in raw_lines(vcf) {
+            match self.match_line(&line) {
+                Some((index, value, _)) if !self.padded_out(index) => {
                     records.0.push(index);
-                    records.1.push(alternative.into());
+                    records.1.push(value);
+                }
+                Some(_) => {
+                    dropped += 1;
+                    unlisted.extend(
+                        split_cells(&line)
+                            .get(2)
+                            .and_then(|id| parse_cell::<RsId>(id)),
+                    );
+                }
+                None if !line.starts_with(b"#") => {
+                    unlisted.extend(
+                        split_cells(&line)
+                            .get(2)
+                            .and_then(|id| parse_cell::<RsId>(id)),
+                    );
                 }
                 None => {}
             }
         }
 
-        Self(records)
+        let indices = records.0.iter().collect::<HashSet<_>>();
+        let (mut matched, mut absent): (Vec<_>, Vec<_>) = self
+            .filter
+            .iter()
+            .partition(|(_, index)| indices.contains(&index.0));
+        matched.sort_unstable();
+        absent.sort_unstable();
+        let mut unlisted = unlisted.into_iter().collect::<Vec<_>>();
+        unlisted.sort_unstable();
+        let report = MatchReport {
+            matched: matched.into_iter().map(|(&rsid, _)| rsid).collect(),
+            absent: absent.into_iter().map(|(&rsid, _)| rsid).collect(),
+            unlisted,
+        };
+
+        let stats = self.match_stats(&records.0, dropped);
+        (RsIdPoly(records), stats, report)
+    }
+}
+
+impl<F> RsIdPoly<F> {
+    /// The highest index with a value, `None` if nothing matched.
+    pub fn max_index(&self) -> Option<Index> {
+        self.0 .0.iter().copied().max().map(Index)
+    }
+
+    /// The parsed `(index, value)` pairs, in VCF order; an index repeats if
+    /// several rsids matched it, and the committed value is their sum.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &F)> {
+        let (indices, values) = &self.0;
+        indices.iter().copied().zip(values)
+    }
+}
+
+impl<F: Field> RsIdPoly<F> {
+    /// The value committed at `index`, 0 if no rsid matched it.
+    pub fn value_at(&self, index: Index) -> F {
+        let (indices, values) = &self.0;
+        indices
+            .iter()
+            .zip(values)
+            .filter(|(&i, _)| i == index.0)
+            .map(|(_, &x)| x)
+            .sum()
+    }
+}
+
+impl<F: From<u8>> RsIdPoly<F> {
+    pub fn from_file(
+        vcf: impl Read,
+        filter: impl RsIdFilter,
+        pad_to: Option<usize>,
+    ) -> (Self, MatchStats) {
+        RsIdPolyBuilder::new(filter).pad_to(pad_to).from_file(vcf)
+    }
+
+    /// Builds the polynomial from already encoded values, without a VCF; rsids
+    /// outside `filter` are skipped with a warning each.
+    pub fn from_genotypes(filter: &impl RsIdFilter, genotypes: &[(RsId, u8)]) -> Self {
+        let (indices, values) = genotypes
+            .iter()
+            .filter_map(|&(rsid, value)| match filter.index_of(rsid) {
+                Some(index) => Some((index.0, F::from(value))),
+                None => {
+                    warn!("{} is not in the rsid list and is not committed", rsid);
+                    None
+                }
+            })
+            .unzip();
+        RsIdPoly((indices, values))
+    }
+}
+
+/// Commits to in-memory genotypes, already encoded, with indices taken from
+/// `filter`; see [`prove_rsid`] for a round trip.
+pub fn commit_rsids<E: Pairing>(
+    pp: &PublicParameters<E>,
+    filter: &impl RsIdFilter,
+    genotypes: &[(RsId, u8)],
+) -> Result<RsIdHash<E>, Error> {
+    RsIdHash::new(pp, &RsIdPoly::from_genotypes(filter, genotypes))
+}
+
+/// Opens the commitment [`commit_rsids`] makes to `genotypes` at `rsid`; an
+/// `Err` if `rsid` is not in `filter`.
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use ark_bls12_381::{Bls12_381, Fr};
+/// use dna_proofs::dna::{commit_rsids, prove_rsid};
+/// use dna_proofs::{Index, PublicParameters, RsId};
+///
+/// let pp = PublicParameters::<Bls12_381>::new(&mut rand::thread_rng(), 4);
+/// let filter = HashMap::from([(RsId(42), Index(0)), (RsId(7), Index(1))]);
+/// let genotypes = [(RsId(42), 2), (RsId(7), 1)];
+///
+/// let hash = commit_rsids(&pp, &filter, &genotypes).unwrap();
+/// let proof = prove_rsid(&pp, &filter, &genotypes, RsId(7)).unwrap();
+/// let outcome = proof.verify(&pp, &hash.commitment(), 1, Fr::from(1u8));
+/// assert!(outcome.unwrap().is_valid());
+/// ```
+pub fn prove_rsid<E: Pairing>(
+    pp: &PublicParameters<E>,
+    filter: &impl RsIdFilter,
+    genotypes: &[(RsId, u8)],
+    rsid: RsId,
+) -> Result<PointProof<E>, Error> {
+    let index = filter.index_of(rsid).ok_or(Error::RsIdNotFound(rsid))?;
+    RsIdHash::prove(pp, &RsIdPoly::from_genotypes(filter, genotypes), index)
+}
+
+impl<F: PrimeField> RsIdPoly<F> {
+    /// Reads precomputed per-rsid values, such as scores, from `rs<id><TAB><value>`
+    /// lines, with values signed decimals below the field modulus; no encoding
+    /// is applied. Rsids outside `filter` are skipped, as are empty lines and
+    /// `#` comments; any other malformed line is an error.
+    pub fn from_tsv(tsv: impl Read, filter: impl RsIdFilter) -> Result<(Self, MatchStats), Error> {
+        let mut records: (Vec<usize>, Vec<F>) = (Vec::new(), Vec::new());
+        for (x, line) in BufReader::new(tsv).lines().enumerate() {
+            let line = line.inspect_err(|_| {
+                error!("Error reading line {} of the tsv", x + 1);
+            })?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (rsid, value) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [rsid, value] => (rsid, value),
+                _ => {
+                    error!("Expected rs<id><TAB><value> on line {} of the tsv", x + 1);
+                    return Err(Error::Parse { line: x + 1 });
+                }
+            };
+            let rsid = rsid.parse::<RsId>().map_err(|_| {
+                error!("Invalid rsid {} on line {} of the tsv", rsid, x + 1);
+                Error::Parse { line: x + 1 }
+            })?;
+            let value = field_from_signed_decimal::<F>(value).map_err(|_| {
+                error!(
+                    "Invalid value {} on line {} of the tsv: expected a decimal below the modulus",
+                    value,
+                    x + 1
+                );
+                Error::Parse { line: x + 1 }
+            })?;
+            if let Some(index) = filter.index_of(rsid) {
+                records.0.push(index.0);
+                records.1.push(value);
+            }
+        }
+
+        let stats = MatchStats {
+            matched: records.0.iter().collect::<HashSet<_>>().len(),
+            total: filter.total(),
+            dropped: 0,
+        };
+        Ok((RsIdPoly(records), stats))
+    }
+
+    /// Reads a 23andMe (`rsid chromosome position genotype`, e.g. `AG`) or
+    /// AncestryDNA (`rsid chromosome position allele1 allele2`) raw data export.
+    /// These carry no reference allele, so a call commits to both of its alleles
+    /// through `encoding`, see [`genotype_value`]. No-calls (`--`, `0 0`), indels
+    /// and the vendors' internal `i<id>` markers are skipped like absent records,
+    /// as are rsids outside `filter`, `#` comments and the AncestryDNA column
+    /// header.
+    pub fn from_23andme(
+        raw: impl Read,
+        filter: impl RsIdFilter,
+        encoding: fn(&[u8]) -> F,
+    ) -> Result<(Self, MatchStats), Error> {
+        let mut records: (Vec<usize>, Vec<F>) = (Vec::new(), Vec::new());
+        for (x, line) in BufReader::new(raw).lines().enumerate() {
+            let line = line.inspect_err(|_| {
+                error!("Error reading line {} of the raw data", x + 1);
+            })?;
+            if line.trim().is_empty() || line.starts_with('#') || line.starts_with("rsid") {
+                continue;
+            }
+            let (rsid, genotype) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [rsid, _, _, genotype] => (rsid, genotype.to_string()),
+                [rsid, _, _, first, second] => (rsid, format!("{}{}", first, second)),
+                _ => {
+                    error!(
+                        "Expected rsid, chromosome, position and genotype on line {} of the raw data",
+                        x + 1
+                    );
+                    return Err(Error::Parse { line: x + 1 });
+                }
+            };
+            let Ok(rsid) = rsid.parse::<RsId>() else {
+                continue;
+            };
+            if !matches!(genotype.len(), 1 | 2) || !genotype.bytes().all(|b| b"ACGT".contains(&b)) {
+                continue;
+            }
+            if let Some(index) = filter.index_of(rsid) {
+                records.0.push(index.0);
+                records
+                    .1
+                    .push(genotype_value(encoding, genotype.as_bytes()));
+            }
+        }
+
+        let stats = MatchStats {
+            matched: records.0.iter().collect::<HashSet<_>>().len(),
+            total: filter.total(),
+            dropped: 0,
+        };
+        Ok((RsIdPoly(records), stats))
+    }
+}
+
+impl<F> GenotypeMatrix<F> {
+    pub fn new(filter: HashMap<RsId, Index>, rows: Vec<RsIdPoly<F>>) -> Self {
+        Self { filter, rows }
+    }
+
+    /// Reads one row per VCF with `builder`, in parallel across samples.
+    pub fn from_files<R: Read + Send>(builder: &RsIdPolyBuilder<F>, vcfs: Vec<R>) -> Self
+    where
+        F: Send + Sync,
+    {
+        let rows = vcfs
+            .into_par_iter()
+            .map(|vcf| builder.from_file(vcf).0)
+            .collect();
+        Self::new(builder.filter.clone(), rows)
+    }
+
+    pub fn samples(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn panel(&self) -> (Vec<usize>, Vec<F>)
+    where
+        F: From<u64>,
+    {
+        self.filter
+            .iter()
+            .map(|(rsid, index)| (index.0, F::from(rsid.0)))
+            .unzip()
+    }
+}
+
+impl<E: Pairing> GenotypeMatrixHash<E> {
+    /// Commits to every row, in parallel across samples, and to the panel.
+    pub fn new(
+        pp: &PublicParameters<E>,
+        matrix: &GenotypeMatrix<E::ScalarField>,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            samples: matrix
+                .rows
+                .par_iter()
+                .map(|row| pp.commit_sparse(&row.0))
+                .collect::<Result<_, _>>()
+                .map_err(Error::IndexNotFound)?,
+            panel: pp
+                .commit_sparse(&matrix.panel())
+                .map_err(Error::IndexNotFound)?,
+        })
+    }
+
+    /// Opens the row of `sample` at the index of `rsid`, together with the panel
+    /// at that index.
+    pub fn prove_cell(
+        pp: &PublicParameters<E>,
+        matrix: &GenotypeMatrix<E::ScalarField>,
+        sample: usize,
+        rsid: RsId,
+    ) -> Result<CellProof<E>, Error> {
+        let index = *matrix.filter.get(&rsid).ok_or_else(|| {
+            error!("{} is not in the panel", rsid);
+            Error::RsIdNotFound(rsid)
+        })?;
+        let row = matrix.rows.get(sample).ok_or_else(|| {
+            error!("No sample {} in a matrix of {}", sample, matrix.samples());
+            Error::IndexNotFound(sample)
+        })?;
+        Ok(CellProof {
+            value: PointProof::new_sparse(pp, &row.0, index.0)?,
+            rsid: PointProof::new_sparse(pp, &matrix.panel(), index.0)?,
+        })
+    }
+
+    /// Checks that `sample` has `value` at `index`, and that `index` holds `rsid`
+    /// in the panel.
+    pub fn verify_cell(
+        &self,
+        pp: &PublicParameters<E>,
+        proof: &CellProof<E>,
+        sample: usize,
+        index: Index,
+        rsid: RsId,
+        value: E::ScalarField,
+    ) -> Result<VerifyOutcome, Error> {
+        let row = self
+            .samples
+            .get(sample)
+            .ok_or(Error::IndexNotFound(sample))?;
+        let value = proof.value.verify(pp, row, index.0, value)?;
+        let rsid = proof.rsid.verify(pp, &self.panel, index.0, rsid.0.into())?;
+        Ok((value.is_valid() && rsid.is_valid()).into())
+    }
+}
+
+impl<E: Pairing> From<RsIdHash<E>> for Commitment<E> {
+    fn from(value: RsIdHash<E>) -> Self {
+        value.0
+    }
+}
+
+impl<E: Pairing> From<&RsIdHash<E>> for Commitment<E> {
+    fn from(value: &RsIdHash<E>) -> Self {
+        value.0
+    }
+}
+
+#[test]
+fn test_qualified_proof() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let vcf = "#CHROM\tPOS\tID\tREF\tALT\tQUAL\n\
+               1\t100\trs1\tA\tG\t50\n\
+               1\t200\trs2\tC\tT\t7.6\n";
+    let filter = HashMap::from([(RsId(1), Index(0)), (RsId(2), Index(1))]);
+    let (poly, stats) = RsIdPolyBuilder::<F>::new(filter).qualified_from_file(vcf.as_bytes());
+    assert_eq!(
+        stats,
+        MatchStats {
+            matched: 2,
+            total: 2,
+            dropped: 0
+        }
+    );
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 2);
+    let hash = QualifiedRsIdHash::new(&pp, &poly).unwrap();
+
+    let proof = QualifiedRsIdHash::prove(&pp, &poly, Index(0)).unwrap();
+    assert_eq!(
+        proof
+            .verify(&pp, &hash, Index(0), F::from(2u8), 50, 30)
+            .unwrap(),
+        VerifyOutcome::Valid
+    );
+    assert_eq!(
+        proof
+            .verify(&pp, &hash, Index(0), F::from(1u8), 50, 30)
+            .unwrap(),
+        VerifyOutcome::Invalid
+    );
+    assert_eq!(
+        proof
+            .verify(&pp, &hash, Index(0), F::from(2u8), 60, 30)
+            .unwrap(),
+        VerifyOutcome::Invalid
+    );
+
+    let proof = QualifiedRsIdHash::prove(&pp, &poly, Index(1)).unwrap();
+    assert_eq!(
+        proof
+            .verify(&pp, &hash, Index(1), F::from(1u8), 8, 0)
+            .unwrap(),
+        VerifyOutcome::Valid
+    );
+    assert_eq!(
+        proof
+            .verify(&pp, &hash, Index(1), F::from(1u8), 8, 30)
+            .unwrap(),
+        VerifyOutcome::Invalid
+    );
+}
+
+#[test]
+fn test_gvcf() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    let vcf = "1\t100\trs1\tA\tG,<NON_REF>\t50\n\
+               1\t101\t.\tA\t<NON_REF>\t.\n\
+               1\t102\trs2\tC\t<NON_REF>\t.\n\
+               1\t103\trs3\n\
+               1\t104\trs\tA\tG\t.\n";
+    let filter = HashMap::from([
+        (RsId(1), Index(0)),
+        (RsId(2), Index(1)),
+        (RsId(3), Index(2)),
+    ]);
+    let (poly, stats) = RsIdPoly::<F>::from_file(vcf.as_bytes(), filter, None);
+
+    assert_eq!(stats.matched, 2);
+    assert_eq!(poly.0 .0, vec![0, 1]);
+    assert_eq!(poly.0 .1, vec![F::from(2u8), F::from(0u8)]);
+}
+
+#[test]
+fn test_crlf() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    let lf = "#CHROM\tPOS\tID\tREF\tALT\n1\t100\trs1\tA\tG\n1\t200\trs2\t C \tT\n";
+    let crlf = lf.replace('\n', "\r\n");
+    let filter = read_rsid_list("rs1\r\nrs2 \r\n".as_bytes()).unwrap();
+    assert_eq!(
+        filter,
+        HashMap::from([(RsId(1), Index(0)), (RsId(2), Index(1))])
+    );
+
+    let builder = RsIdPolyBuilder::<F>::new(filter);
+    let (poly, stats) = builder.from_file(crlf.as_bytes());
+    assert_eq!(stats.matched, 2);
+    assert_eq!(poly.0 .1, vec![F::from(2u8), F::from(1u8)]);
+    assert_eq!(poly.0, builder.from_file(lf.as_bytes()).0 .0);
+
+    assert_eq!(poly.0, builder.par_from_reader(crlf.as_bytes(), 1, 1).0 .0);
+}
+
+#[test]
+fn test_parallel_parse() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let vcf = include_str!("../example.vcf");
+    let filter = read_rsid_list(include_str!("../rsidlist").as_bytes()).unwrap();
+    let builder = RsIdPolyBuilder::<F>::new(filter);
+
+    let (serial, serial_stats) = builder.from_file(vcf.as_bytes());
+    // several batches of several chunks
+    let (parallel, parallel_stats) = builder.par_from_reader(vcf.as_bytes(), 100, 3);
+    assert_eq!(serial_stats, parallel_stats);
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 6);
+    let mut serial_bytes = Vec::new();
+    let mut parallel_bytes = Vec::new();
+    RsIdHash::new(&pp, &serial)
+        .unwrap()
+        .serialize_compressed(&mut serial_bytes)
+        .unwrap();
+    RsIdHash::new(&pp, &parallel)
+        .unwrap()
+        .serialize_compressed(&mut parallel_bytes)
+        .unwrap();
+    assert_eq!(serial_bytes, parallel_bytes);
+}
+
+#[test]
+fn test_info_field() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    let vcf = "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+               1\t100\trs1\tA\tG\t50\tPASS\tDP=10;AF=0.25\n\
+               1\t200\trs2\tC\tT,A\t50\tPASS\tAF=0.5,0.125;DB\n\
+               1\t300\trs3\tC\tT\t50\tPASS\tDP=3\n\
+               1\t400\trs4\tC\tT\t50\tPASS\tAF=abc;AF\n\
+               1\t500\trs5\tC\tT\n";
+    let filter = (1..=5)
+        .map(|i| (RsId(i), Index(i as usize - 1)))
+        .collect::<HashMap<_, _>>();
+    let fallback = F::from(7u64);
+    let (poly, af, stats) =
+        RsIdPolyBuilder::<F>::new(filter).info_from_file(vcf.as_bytes(), "AF", 1000, fallback);
+    assert_eq!(stats.matched, 5);
+    assert_eq!(poly.0 .0, vec![0, 1, 2, 3, 4]);
+    assert_eq!(
+        af,
+        vec![
+            F::from(250u64),
+            F::from(500u64),
+            fallback,
+            fallback,
+            fallback
+        ]
+    );
+}
+
+#[test]
+fn test_commit_stream() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 6);
+    let vcf = include_str!("../example.vcf");
+    let filter = read_rsid_list(include_str!("../rsidlist").as_bytes()).unwrap();
+    let builder = RsIdPolyBuilder::<F>::new(filter);
+
+    let (poly, stats) = builder.from_file(vcf.as_bytes());
+    for window in [1, 7, 1 << 20] {
+        let (commitment, stream_stats) =
+            builder.commit_stream(&pp, vcf.as_bytes(), window).unwrap();
+        assert_eq!(commitment, pp.commit_sparse(&poly.0).unwrap());
+        assert_eq!(stream_stats, stats);
     }
 }
 
-impl<E: Pairing, B: Borrow<RsIdHash<E>>> From<B> for Commitment<E> {
-    fn from(value: B) -> Self {
-        value.borrow().0
+#[test]
+fn test_rsid_hash_golden() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = crate::commitment::golden_parameters();
+    let poly = RsIdPoly((
+        vec![0, 2, 3],
+        vec![F::from(1u8), F::from(2u8), F::from(1u8)],
+    ));
+    let mut bytes = Vec::new();
+    RsIdHash::<E>::new(&pp, &poly)
+        .unwrap()
+        .serialize_compressed(&mut bytes)
+        .unwrap();
+    assert_eq!(
+        hex::encode(bytes),
+        "8e42e86afe589c69f47b0ad59cfa6c25346df78d934c71fcda7ef4c518158ec8\
+         f46bf06d38360d26f8b23fb77522ef24"
+    );
+}
+
+#[test]
+fn test_genotype_matrix() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let filter = HashMap::from([(RsId(7), Index(0)), (RsId(3), Index(1))]);
+    let builder = RsIdPolyBuilder::<F>::new(filter);
+    let vcfs = vec![
+        "1\t100\trs7\tA\tG\n1\t200\trs3\tC\tT\n".as_bytes(),
+        "1\t200\trs3\tC\tC\n".as_bytes(),
+    ];
+    let matrix = GenotypeMatrix::from_files(&builder, vcfs);
+    assert_eq!(matrix.samples(), 2);
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 2);
+    let hash = GenotypeMatrixHash::new(&pp, &matrix).unwrap();
+
+    let proof = GenotypeMatrixHash::prove_cell(&pp, &matrix, 1, RsId(3)).unwrap();
+    let verify = |sample, rsid, value: u8| {
+        hash.verify_cell(&pp, &proof, sample, Index(1), RsId(rsid), F::from(value))
+    };
+    assert_eq!(verify(1, 3, 2).unwrap(), VerifyOutcome::Valid);
+    assert_eq!(verify(1, 7, 2).unwrap(), VerifyOutcome::Invalid);
+    assert_eq!(verify(0, 3, 2).unwrap(), VerifyOutcome::Invalid);
+    assert!(verify(2, 3, 2).is_err());
+
+    assert!(GenotypeMatrixHash::prove_cell(&pp, &matrix, 0, RsId(5)).is_err());
+    assert!(GenotypeMatrixHash::prove_cell(&pp, &matrix, 2, RsId(3)).is_err());
+}
+
+#[test]
+fn test_hash_bucket_filter() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let vcf = "1\t100\trs1\tA\tG\n1\t200\trs2\tC\tT\n1\t300\tfoo\tC\tT\n";
+    let filter = HashBucketFilter::new(1 << 10);
+    let (poly, stats) = RsIdPoly::<F>::from_file(vcf.as_bytes(), filter, None);
+    assert_eq!(stats.matched, 2);
+    let (i, j) = (
+        filter.index_of(RsId(1)).unwrap(),
+        filter.index_of(RsId(2)).unwrap(),
+    );
+    assert_eq!(poly.0 .0, vec![i.0, j.0]);
+
+    // everything collides in a single bucket and is summed
+    let filter = HashBucketFilter::with_hash(1, |rsid| rsid.0);
+    let (poly, stats) = RsIdPoly::<F>::from_file(vcf.as_bytes(), filter, None);
+    assert_eq!(stats.matched, 1);
+    assert_eq!(poly.value_at(Index(0)), F::from(3u8));
+}
+
+#[test]
+fn test_dna_prove_many() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 6);
+    let mut vcf = DnaPoly::<F>(Default::default());
+    vcf.0[0] = (
+        vec![3, 10, 40],
+        vec![F::from(1u8), F::from(2u8), F::from(1u8)],
+    );
+    vcf.0[22] = (vec![7, 8], vec![F::from(2u8), F::from(2u8)]);
+    let hash = DnaHash::new(&pp, &vcf).unwrap();
+
+    let indices = [(22, 8), (0, 40), (0, 3), (5, 1), (22, 8)];
+    let proofs = DnaHash::prove_many(&pp, &vcf, &indices).unwrap();
+    for (&(chromosome, position), proof) in indices.iter().zip(&proofs) {
+        let mut many = Vec::new();
+        let mut single = Vec::new();
+        proof.serialize_compressed(&mut many).unwrap();
+        DnaHash::prove(&pp, &vcf, (chromosome, position))
+            .unwrap()
+            .serialize_compressed(&mut single)
+            .unwrap();
+        assert_eq!(many, single);
     }
+    assert!(proofs[1]
+        .verify(&pp, hash.chromosome(0).unwrap(), 40, F::from(1u8))
+        .unwrap()
+        .is_valid());
+    assert!(DnaHash::prove_many(&pp, &vcf, &[(CHROMOSOMES, 0)]).is_err());
+    assert!(hash.chromosome(CHROMOSOMES).is_none());
+    assert_eq!(hash.chromosomes().count(), CHROMOSOMES);
+}
+
+#[test]
+fn test_chromosome_to_int() {
+    assert_eq!(chromosome_to_int(b"1"), Ok(1));
+    assert_eq!(chromosome_to_int(b"chr22"), Ok(22));
+    assert_eq!(chromosome_to_int(b"X"), Ok(23));
+    assert_eq!(chromosome_to_int(b"chrY"), Ok(24));
+    assert_eq!(chromosome_to_int(b"chrM"), Ok(25));
+    assert_eq!(chromosome_to_int(b"MT"), Ok(25));
+    assert!(chromosome_to_int(b"0").is_err());
+    assert!(chromosome_to_int(b"23").is_err());
+    assert!(chromosome_to_int(b"chrUn_gl000220").is_err());
+
+    let vcf = "#CHROM\tPOS\tID\tREF\tALT\n\
+               chrX\t5\trs1\tA\tG\n\
+               chrUn\t6\trs2\tA\tG\n";
+    let poly = DnaPoly::<u8>::from_file(vcf.as_bytes());
+    assert_eq!(poly.0[22].0, [5]);
+    assert_eq!(
+        poly.0
+            .iter()
+            .map(|(positions, _)| positions.len())
+            .sum::<usize>(),
+        1
+    );
+}
+
+#[test]
+fn test_non_utf8() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    let filter = HashMap::from([(RsId(1), Index(0)), (RsId(2), Index(1))]);
+    let vcf = b"##comment \xff\xfe\n\
+        1\t100\trs1\tA\tG\t50\tPASS\tAF=0.5;NOTE=\xc3\x28\n\
+        1\t200\trs2\tC\tT\t\xff\tPASS\tAF=0.25\n";
+
+    let builder = RsIdPolyBuilder::<F>::new(filter);
+    let (poly, stats) = builder.from_file(&vcf[..]);
+    assert_eq!(stats.matched, 2);
+    assert_eq!(poly.0 .1, vec![F::from(2u8), F::from(1u8)]);
+
+    let (qualified, _) = builder.qualified_from_file(&vcf[..]);
+    assert_eq!(qualified.quality.0 .1, vec![F::from(50u8), F::from(0u8)]);
+    let (_, info, _) = builder.info_from_file(&vcf[..], "AF", 100, F::from(0u8));
+    assert_eq!(info, vec![F::from(50u8), F::from(25u8)]);
+}
+
+#[test]
+fn test_explicit_indices() {
+    let filter = read_rsid_list("rs1\t7\nrs2\t3\r\n".as_bytes()).unwrap();
+    assert_eq!(
+        filter,
+        HashMap::from([(RsId(1), Index(7)), (RsId(2), Index(3))])
+    );
+
+    assert!(read_rsid_list("rs1\t7\nrs2\t7\n".as_bytes()).is_err());
+    assert!(read_rsid_list("rs1\t7\nrs1\t8\n".as_bytes()).is_err());
+    assert!(read_rsid_list("rs1\t7\nrs2\n".as_bytes()).is_err());
+    assert!(read_rsid_list("rs1\nrs2\t1\n".as_bytes()).is_err());
+}
+
+#[test]
+fn test_rsid_list() {
+    let filter = read_rsid_list("rs1\n\n  \n2\r\nrs3\n\n".as_bytes()).unwrap();
+    assert_eq!(
+        filter,
+        HashMap::from([
+            (RsId(1), Index(0)),
+            (RsId(2), Index(1)),
+            (RsId(3), Index(2))
+        ])
+    );
+
+    assert!(matches!(
+        read_rsid_list("rs1\nrs\n".as_bytes()),
+        Err(Error::Parse { line: 2 })
+    ));
+    assert!(read_rsid_list("rs1\nrsx\n".as_bytes()).is_err());
+}
+
+#[test]
+fn test_merge_table() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    let merges = read_merge_table("rs999\trs1\nrs2\trs3\n".as_bytes()).unwrap();
+    assert!(read_merge_table("rs999\n".as_bytes()).is_err());
+
+    let filter = HashMap::from([(RsId(1), Index(0)), (RsId(2), Index(1))]);
+    let vcf = "1\t100\trs999\tA\tG\n1\t200\trs2\tC\tT\n";
+    let builder = RsIdPolyBuilder::<F>::new(filter).merge_table(merges);
+    let (poly, stats) = builder.from_file(vcf.as_bytes());
+    assert_eq!(stats.matched, 2);
+    // rs2 is in the list itself and is not redirected
+    assert_eq!(poly.0 .0, vec![0, 1]);
+}
+
+#[test]
+fn test_match_report() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    let filter = HashMap::from([
+        (RsId(1), Index(0)),
+        (RsId(2), Index(1)),
+        (RsId(3), Index(2)),
+    ]);
+    let vcf = "#CHROM\tPOS\tID\tREF\tALT\n\
+               1\t100\trs3\tA\tG\n\
+               1\t150\t.\tA\tG\n\
+               1\t200\trs9\tC\tT\n\
+               1\t300\trs1\tC\tT\n";
+    let builder = RsIdPolyBuilder::<F>::new(filter);
+    let (poly, stats, report) = builder.from_file_with_report(vcf.as_bytes());
+    assert_eq!(poly.0, builder.from_file(vcf.as_bytes()).0 .0);
+    assert_eq!(stats.matched, 2);
+    assert_eq!(report.matched, vec![RsId(1), RsId(3)]);
+    assert_eq!(report.absent, vec![RsId(2)]);
+    assert_eq!(report.unlisted, vec![RsId(9)]);
+}
+
+#[test]
+fn test_aggregate_dna_proof() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 6);
+    let mut vcf = DnaPoly::<F>(Default::default());
+    vcf.0[1] = (vec![3, 10], vec![F::from(1u8), F::from(2u8)]);
+    vcf.0[7] = (vec![5], vec![F::from(2u8)]);
+    let hash = DnaHash::new(&pp, &vcf).unwrap();
+
+    let proof = DnaHash::prove_aggregate(&pp, &vcf, &[(1, 10), (7, 5), (7, 6)]).unwrap();
+    assert_eq!(
+        proof.openings(),
+        &[
+            (1, 10, F::from(2u8)),
+            (7, 5, F::from(2u8)),
+            (7, 6, F::from(0u8))
+        ]
+    );
+    assert_eq!(
+        proof.verify(&pp, &hash, &mut rand::thread_rng()).unwrap(),
+        VerifyOutcome::Valid
+    );
+
+    // an opening moved to another chromosome no longer verifies
+    let mut moved = proof;
+    moved.openings[1].0 = 1;
+    assert_eq!(
+        moved.verify(&pp, &hash, &mut rand::thread_rng()).unwrap(),
+        VerifyOutcome::Invalid
+    );
+}
+
+#[test]
+fn test_index_past_parameters() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 3);
+    let fits = RsIdPoly((vec![0, 7], vec![F::from(1u8), F::from(2u8)]));
+    assert!(RsIdHash::new(&pp, &fits).is_ok());
+    let too_long = RsIdPoly((vec![0, 8], vec![F::from(1u8), F::from(2u8)]));
+    assert!(matches!(
+        RsIdHash::new(&pp, &too_long),
+        Err(Error::IndexNotFound(8))
+    ));
+
+    let mut vcf = DnaPoly::<F>(Default::default());
+    vcf.0[3] = (vec![100], vec![F::from(1u8)]);
+    assert!(DnaHash::new(&pp, &vcf).is_err());
+}
+
+#[test]
+fn test_prove_absent() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 3);
+    let poly = RsIdPoly((vec![1, 5], vec![F::from(2u8), F::from(0u8)]));
+    let hash = RsIdHash::new(&pp, &poly).unwrap();
+
+    let proof = RsIdHash::prove_absent(&pp, &poly, Index(3)).unwrap();
+    let verify = |value: F| proof.verify(&pp, &hash.commitment(), 3, value);
+    assert_eq!(verify(F::from(0u8)), Ok(VerifyOutcome::Valid));
+    assert_eq!(verify(F::from(1u8)), Ok(VerifyOutcome::Invalid));
+
+    // populated, even with 0, or past the parameters
+    assert!(RsIdHash::prove_absent(&pp, &poly, Index(1)).is_err());
+    assert!(RsIdHash::prove_absent(&pp, &poly, Index(5)).is_err());
+    assert!(RsIdHash::prove_absent(&pp, &poly, Index(8)).is_err());
+}
+
+#[test]
+fn test_commit_and_open() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 3);
+    let poly = RsIdPoly((
+        vec![1, 2, 6],
+        vec![F::from(2u8), F::from(1u8), F::from(2u8)],
+    ));
+    let indices = [Index(6), Index(1), Index(4)];
+    let (hash, proofs) = RsIdHash::commit_and_open(&pp, &poly, &indices).unwrap();
+    assert_eq!(hash, RsIdHash::new(&pp, &poly).unwrap());
+    for (index, proof) in indices.iter().zip(&proofs) {
+        assert_eq!(
+            proof.verify(&pp, &hash.commitment(), index.0, poly.value_at(*index)),
+            Ok(VerifyOutcome::Valid)
+        );
+    }
+    assert!(matches!(
+        RsIdHash::commit_and_open(&pp, &poly, &[Index(8)]),
+        Err(Error::IndexNotFound(8))
+    ));
+}
+
+#[test]
+fn test_rsid_hash_update() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 3);
+    let poly = RsIdPoly((vec![1, 4], vec![F::from(2u8), F::from(1u8)]));
+    let mut hash = RsIdHash::new(&pp, &poly).unwrap();
+    hash.update(&pp, Index(4), F::from(1u8), F::from(2u8))
+        .unwrap();
+    let recalled = RsIdPoly((vec![1, 4], vec![F::from(2u8), F::from(2u8)]));
+    assert_eq!(hash, RsIdHash::new(&pp, &recalled).unwrap());
+
+    // the updated hash no longer commits to the original polynomial
+    assert!(RsIdHash::prove_checked(&pp, &recalled, &hash, Index(4)).is_ok());
+    assert!(matches!(
+        RsIdHash::prove_checked(&pp, &poly, &hash, Index(4)),
+        Err(Error::VerificationFailed)
+    ));
+}
+
+#[test]
+fn test_from_tsv() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    let filter = HashMap::from([(RsId(1), Index(0)), (RsId(2), Index(1))]);
+    let tsv = "# rsid\tscore\nrs1\t1234567890123456789012\nrs2\t-3\r\nrs9\t1\n\n";
+    let (poly, stats) = RsIdPoly::<F>::from_tsv(tsv.as_bytes(), filter.clone()).unwrap();
+    assert_eq!(stats.matched, 2);
+    assert_eq!(
+        poly.value_at(Index(0)),
+        crate::commitment::field_from_decimal("1234567890123456789012").unwrap()
+    );
+    assert_eq!(poly.value_at(Index(1)), -F::from(3u8));
+
+    assert!(RsIdPoly::<F>::from_tsv("rs1\tx\n".as_bytes(), filter.clone()).is_err());
+    assert!(RsIdPoly::<F>::from_tsv("rs1\n".as_bytes(), filter.clone()).is_err());
+    let modulus = F::MODULUS.to_string();
+    let too_large = format!("rs1\t{}\n", modulus);
+    assert!(RsIdPoly::<F>::from_tsv(too_large.as_bytes(), filter).is_err());
+}
+
+#[test]
+fn test_distinct_encoding() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    assert_eq!(base_to_int(b"C"), base_to_int(b"G"));
+    let values = ["A", "C", "G", "T", "N"].map(|base| distinct_base_to_int(base.as_bytes()));
+    assert_eq!(values, [1, 2, 3, 4, 0]);
+
+    let filter = HashMap::from([(RsId(1), Index(0)), (RsId(2), Index(1))]);
+    let vcf = "1\t100\trs1\tA\tC\t50\tPASS\t.\n1\t200\trs2\tA\tG\t50\tPASS\t.\n";
+    let encoding = value_encoding(DISTINCT_BASE_ENCODING).unwrap();
+    let (poly, _) =
+        RsIdPolyBuilder::<F>::with_encoding(filter.clone(), encoding).from_file(vcf.as_bytes());
+    assert_eq!(poly.value_at(Index(0)), F::from(2u8));
+    assert_eq!(poly.value_at(Index(1)), F::from(3u8));
+    let (poly, _) = RsIdPolyBuilder::<F>::new(filter).from_file(vcf.as_bytes());
+    assert_eq!(poly.value_at(Index(0)), poly.value_at(Index(1)));
+
+    assert_eq!(
+        parse_value::<F>("G", DISTINCT_BASE_ENCODING).unwrap(),
+        F::from(3u8)
+    );
+    assert_eq!(parse_value::<F>("G", BASE_ENCODING).unwrap(), F::from(2u8));
+    assert_eq!(parse_value::<F>("-1", 9).unwrap(), -F::from(1u8));
+    assert!(parse_value::<F>("G", 9).is_err());
+}
+
+#[test]
+fn test_allele_hash() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    let filter = (1..=5)
+        .map(|i| (RsId(i), Index(i as usize - 1)))
+        .collect::<HashMap<_, _>>();
+    let vcf = "1\t100\trs1\tA\tG\t50\tPASS\t.\n\
+               1\t200\trs2\tA\tAT\t50\tPASS\t.\n\
+               1\t300\trs3\tAT\tA\t50\tPASS\t.\n\
+               1\t400\trs4\tA\tAG\t50\tPASS\t.\n\
+               1\t500\trs5\tC\t<DEL>\t50\tPASS\t.\n";
+    let (poly, stats) = RsIdPolyBuilder::<F>::new(filter)
+        .allele_hash()
+        .from_file(vcf.as_bytes());
+    assert_eq!(stats.matched, 5);
+    assert_eq!(poly.value_at(Index(0)), F::from(3u8));
+    let values = (1..5)
+        .map(|i| poly.value_at(Index(i)))
+        .collect::<HashSet<_>>();
+    assert_eq!(values.len(), 4);
+    assert!(!values.iter().any(|value| *value <= F::from(4u8)));
+
+    // an insertion of the deleted base is not the deletion
+    assert_ne!(
+        allele_value::<F>(b"A", b"AT"),
+        allele_value::<F>(b"AT", b"A")
+    );
+    assert_eq!(
+        parse_value::<F>("A>AT", ALLELE_HASH_ENCODING).unwrap(),
+        poly.value_at(Index(1))
+    );
+    assert_eq!(
+        parse_value::<F>("C><DEL>", ALLELE_HASH_ENCODING).unwrap(),
+        poly.value_at(Index(4))
+    );
+    assert!(parse_value::<F>("AT", ALLELE_HASH_ENCODING).is_err());
+}
+
+#[test]
+fn test_from_23andme() {
+    type F = <ark_bls12_381::Bls12_381 as Pairing>::ScalarField;
+
+    let filter = (1..=6)
+        .map(|i| (RsId(i), Index(i as usize - 1)))
+        .collect::<HashMap<_, _>>();
+    let raw = "# This data file generated by 23andMe\n\
+               # rsid\tchromosome\tposition\tgenotype\n\
+               rs1\t1\t82154\tAA\n\
+               rs2\t1\t752566\tAG\n\
+               rs3\t1\t752721\t--\n\
+               i713426\t1\t752894\tCC\n\
+               rs4\tX\t2700157\tC\n\
+               rs5\t2\t2001\tDI\n\
+               rs9\t1\t776546\tAA\n";
+    let (poly, stats) =
+        RsIdPoly::<F>::from_23andme(raw.as_bytes(), filter.clone(), base_encoding).unwrap();
+    assert_eq!(
+        stats,
+        MatchStats {
+            matched: 3,
+            total: 6,
+            dropped: 0
+        }
+    );
+    // A and T are 1, C and G are 2 under the base encoding
+    assert_eq!(poly.value_at(Index(0)), F::from(1u16 + 256));
+    assert_eq!(poly.value_at(Index(1)), F::from(1u16 + 2 * 256));
+    assert_eq!(poly.value_at(Index(2)), F::from(0u8));
+    assert_eq!(poly.value_at(Index(3)), F::from(2u16 + 2 * 256));
+    assert_eq!(
+        genotype_value::<F>(base_encoding, b"GA"),
+        poly.value_at(Index(1))
+    );
+
+    let ancestry = "#AncestryDNA raw data download\n\
+                    rsid\tchromosome\tposition\tallele1\tallele2\n\
+                    rs6\t1\t82154\tG\tG\n\
+                    rs1\t1\t752566\t0\t0\n";
+    let (poly, stats) =
+        RsIdPoly::<F>::from_23andme(ancestry.as_bytes(), filter.clone(), distinct_base_encoding)
+            .unwrap();
+    assert_eq!(stats.matched, 1);
+    assert_eq!(poly.value_at(Index(5)), F::from(3u16 + 3 * 256));
+
+    assert!(matches!(
+        RsIdPoly::<F>::from_23andme("rs1\t1\n".as_bytes(), filter, base_encoding),
+        Err(Error::Parse { line: 1 })
+    ));
+}
+
+#[test]
+fn test_rsid_poly_iter() {
+    let filter = HashMap::from([(RsId(1), Index(1)), (RsId(2), Index(0))]);
+    let poly =
+        RsIdPoly::<u64>::from_genotypes(&filter, &[(RsId(1), 3), (RsId(9), 1), (RsId(2), 4)]);
+    assert_eq!(poly.iter().collect::<Vec<_>>(), [(1, &3), (0, &4)]);
+}
+
+#[test]
+fn test_chromosome_slots() {
+    let names = (1..=22)
+        .map(|n| n.to_string())
+        .chain(["X", "Y", "MT"].map(String::from));
+    let vcf = names
+        .enumerate()
+        .map(|(x, chr)| format!("chr{}\t{}\trs{}\tA\tG\n", chr, 1000 - x, x))
+        .collect::<String>();
+    let poly = DnaPoly::<u8>::from_file(vcf.as_bytes());
+    for (slot, (positions, _)) in poly.0.iter().enumerate() {
+        assert_eq!(positions, &[1000 - slot]);
+    }
+}
+
+#[test]
+fn test_dosage() {
+    let vcf = "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\n\
+               1\t1\trs1\tA\tG\t.\t.\t.\tGT\t0/0\n\
+               1\t2\trs2\tA\tG\t.\t.\t.\tGT:DP\t0|1:12\n\
+               1\t3\trs3\tA\tG\t.\t.\t.\tDP:GT\t9:1/1\n\
+               1\t4\trs4\tA\tG\t.\t.\t.\tGT\t./.\n\
+               1\t5\trs5\tA\tG\n";
+    let filter = (1..=5)
+        .map(|x| (RsId(x), Index(x as usize - 1)))
+        .collect::<HashMap<_, _>>();
+
+    let (alt, _) = RsIdPolyBuilder::<u8>::new(filter.clone()).from_file(vcf.as_bytes());
+    assert_eq!(alt.iter().map(|(_, &x)| x).collect::<Vec<_>>(), [2; 5]);
+
+    let builder = RsIdPolyBuilder::<u8>::new(filter).dosage(None);
+    let (dosage, stats) = builder.from_file(vcf.as_bytes());
+    assert_eq!(
+        dosage.iter().collect::<Vec<_>>(),
+        [(0, &0), (1, &1), (2, &2)]
+    );
+    assert_eq!(stats.matched, 3);
+    let (dosage, _) = builder.dosage(Some(3)).from_file(vcf.as_bytes());
+    assert_eq!(
+        dosage.iter().map(|(_, &x)| x).collect::<Vec<_>>(),
+        [0, 1, 2, 3, 3]
+    );
+}
+
+#[test]
+fn test_pad_to() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let vcf = "1\t1\trs1\tA\tG\n1\t2\trs2\tA\tG\n1\t3\trs3\tA\tG\n1\t4\trs4\tA\tG\n";
+    let filter = (1..=4)
+        .map(|x| (RsId(x), Index(x as usize - 1)))
+        .collect::<HashMap<_, _>>();
+    let builder = RsIdPolyBuilder::<F>::new(filter).pad_to(Some(2));
+
+    let (poly, stats) = builder.from_file(vcf.as_bytes());
+    assert_eq!(poly.max_index(), Some(Index(1)));
+    assert_eq!((stats.matched, stats.dropped), (2, 2));
+    assert_eq!(builder.par_from_reader(vcf.as_bytes(), 1, 2).1, stats);
+    assert_eq!(builder.from_file_with_report(vcf.as_bytes()).1, stats);
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 2);
+    let (commitment, streamed) = builder.commit_stream(&pp, vcf.as_bytes(), 1).unwrap();
+    assert_eq!(streamed, stats);
+    assert_eq!(commitment, pp.commit_sparse(&poly.0).unwrap());
+}
+
+#[test]
+fn test_multi_allelic() {
+    let vcf = "1\t1\trs1\tC\tA,G,T\t.\t.\t.\tGT\t1/2\n\
+               1\t2\trs2\tC\tA,G,T\t.\t.\t.\tGT\t0|2\n\
+               1\t3\trs3\tC\tA,G,T\t.\t.\t.\tGT\t3/2\n\
+               1\t4\trs4\tC\tA,G,T\t.\t.\t.\tGT\t0/0\n\
+               1\t5\trs5\tC\tA,G,<NON_REF>\n\
+               1\t6\trs6\tC\tA,G,T\t.\t.\t.\tGT\t./.\n\
+               1\t7\trs7\tC\tA,G\t.\t.\t.\tGT\t1/3\n\
+               1\t8\trs8\tC\tG\t.\t.\t.\tGT\t0/0\n\
+               1\t9\trs9\tC\tG,T\t.\t.\t.\tGT\t0/0\n";
+    let filter = (1..=9)
+        .map(|x| (RsId(x), Index(x as usize - 1)))
+        .collect::<HashMap<_, _>>();
+    let first_base = |alt: &[u8]| alt[0];
+
+    let builder = RsIdPolyBuilder::<u8>::with_encoding(filter, first_base);
+    let (poly, _) = builder.from_file(vcf.as_bytes());
+    assert_eq!(
+        poly.iter().map(|(_, &x)| x).collect::<Vec<_>>(),
+        b"AGGAAAGG"
+    );
+
+    let builder = builder.multi_allelic(MultiAllelic::Skip);
+    let (poly, _) = builder.from_file(vcf.as_bytes());
+    assert_eq!(poly.iter().map(|(_, &x)| x).collect::<Vec<_>>(), b"AGGG");
+}
+
+#[test]
+fn test_dna_commit_stream() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let vcf = "#CHROM\tPOS\tID\tREF\tALT\n\
+               1\t3\trs1\tA\tG\n\
+               chrX\t5\trs2\tA\tC\n\
+               1\t9\trs3\tA\tT\n\
+               1\t3\trs4\tA\tA\n\
+               MT\t1\trs5\tA\tG\n";
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 4);
+    let hash = DnaHash::new(&pp, &DnaPoly::<F>::from_file(vcf.as_bytes())).unwrap();
+    for window in [1, 2, 100] {
+        assert_eq!(
+            DnaHash::commit_stream(&pp, vcf.as_bytes(), window).unwrap(),
+            hash
+        );
+    }
+    assert!(DnaHash::commit_stream(&pp, "1\t16\trs1\tA\tG\n".as_bytes(), 1).is_err());
+}
+
+#[test]
+fn test_raw_lines_read_error() {
+    struct Failing;
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    let vcf = "1\t5\trs1\tA\tG\n".as_bytes().chain(Failing);
+    assert_eq!(raw_lines(vcf).collect::<Vec<_>>(), [b"1\t5\trs1\tA\tG"]);
+}
+
+#[test]
+fn test_dna_records_malformed() {
+    type F = ark_bls12_381::Fr;
+
+    let vcf = "1\t5\trs1\tA\tG\n\n\
+               1\tfive\trs2\tA\tG\n\
+               1\t6\trs3\n\
+               2\t7\trs4\tA\tT\n";
+    assert_eq!(
+        DnaPoly::<F>::records(vcf.as_bytes()).collect::<Vec<_>>(),
+        [(0, 5, F::from(2u8)), (1, 7, F::from(1u8))]
+    );
+}
+
+#[test]
+fn test_bn254() {
+    type E = ark_bn254::Bn254;
+    type F = <E as Pairing>::ScalarField;
+
+    let vcf = "1\t100\trs1\tA\tG\n1\t200\trs2\tC\tT\n";
+    let filter = HashMap::from([(RsId(1), Index(0)), (RsId(2), Index(1))]);
+    let (poly, _) = RsIdPolyBuilder::<F>::new(filter).from_file(vcf.as_bytes());
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 2);
+    let hash = RsIdHash::new(&pp, &poly).unwrap();
+    let proof = RsIdHash::prove(&pp, &poly, Index(1)).unwrap();
+    assert_eq!(
+        proof.verify(&pp, &hash.commitment(), 1, F::from(1u8)),
+        Ok(VerifyOutcome::Valid)
+    );
+    assert_eq!(
+        proof.verify(&pp, &hash.commitment(), 1, F::from(2u8)),
+        Ok(VerifyOutcome::Invalid)
+    );
 }
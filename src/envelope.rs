@@ -0,0 +1,285 @@
+use alloc::vec::Vec;
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::commitment::{Commitment, PointProof, PublicParameters, VerifyOutcome};
+
+/// Encoding id of values produced by [`base_to_int`](crate::dna::base_to_int).
+pub const BASE_ENCODING: u8 = 0;
+
+/// Encoding id of values produced by
+/// [`distinct_base_to_int`](crate::dna::distinct_base_to_int).
+pub const DISTINCT_BASE_ENCODING: u8 = 1;
+
+/// Encoding id of values produced by [`allele_value`](crate::dna::allele_value),
+/// which hashes indels and structural variants and so has no small range.
+pub const ALLELE_HASH_ENCODING: u8 = 2;
+
+/// Encoding id of allele dosages, see
+/// [`RsIdPolyBuilder::dosage`](crate::dna::RsIdPolyBuilder::dosage): 0 to 2 ALT
+/// alleles called, or [`MISSING_DOSAGE`] for a missing call.
+pub const DOSAGE_ENCODING: u8 = 3;
+
+/// What a missing call commits to under [`DOSAGE_ENCODING`], unless skipped.
+pub const MISSING_DOSAGE: u8 = 3;
+
+/// Whether values under `encoding` can be interpreted here.
+pub fn is_known_encoding(encoding: u8) -> bool {
+    encoding_range(encoding).is_some() || encoding == ALLELE_HASH_ENCODING
+}
+
+/// The values an encoding can produce, `None` for encodings not known here and
+/// for those without a small range, such as [`ALLELE_HASH_ENCODING`].
+pub fn encoding_range(encoding: u8) -> Option<&'static [u8]> {
+    match encoding {
+        BASE_ENCODING => Some(&[0, 1, 2]),
+        DISTINCT_BASE_ENCODING => Some(&[0, 1, 2, 3, 4]),
+        DOSAGE_ENCODING => Some(&[0, 1, 2, MISSING_DOSAGE]),
+        _ => None,
+    }
+}
+
+/// Whether the envelope's value is a possible output of its encoding; values of
+/// unknown encodings are not checked.
+fn in_encoding_range<E: Pairing>(envelope: &ProofEnvelope<E>) -> bool {
+    match encoding_range(envelope.encoding) {
+        Some(range) => range
+            .iter()
+            .any(|&x| E::ScalarField::from(x) == envelope.value),
+        None => true,
+    }
+}
+
+/// Why an envelope could not be checked at all, as opposed to failing to verify.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnvelopeError {
+    /// The proof was made against other public parameters, so it says nothing
+    /// about the statement either way.
+    SrsMismatch,
+    /// The opened index is past the public parameters.
+    IndexOutOfRange,
+}
+
+/// A [`PointProof`] together with what a verifier needs to check it: the opened
+/// index and value, the encoding the value was produced with, and digests of
+/// the SRS and rsid list the proof was made against.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofEnvelope<E: Pairing> {
+    proof: PointProof<E>,
+    index: usize,
+    value: E::ScalarField,
+    encoding: u8,
+    srs_digest: [u8; 32],
+    list_digest: [u8; 32],
+}
+
+impl<E: Pairing> ProofEnvelope<E> {
+    /// `srs_digest` is the [`PublicParameters::digest`] of the parameters the
+    /// proof was made against; it hashes every generator, so compute it once
+    /// when wrapping many proofs.
+    pub fn new(
+        srs_digest: [u8; 32],
+        proof: PointProof<E>,
+        index: usize,
+        value: E::ScalarField,
+        encoding: u8,
+        list_digest: [u8; 32],
+    ) -> Self {
+        Self {
+            proof,
+            index,
+            value,
+            encoding,
+            srs_digest,
+            list_digest,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn value(&self) -> E::ScalarField {
+        self.value
+    }
+
+    pub fn encoding(&self) -> u8 {
+        self.encoding
+    }
+
+    pub fn list_digest(&self) -> &[u8; 32] {
+        &self.list_digest
+    }
+}
+
+/// Digest of an rsid list, as stored in a [`ProofEnvelope`].
+pub fn list_digest(list: &[u8]) -> [u8; 32] {
+    Sha256::digest(list).into()
+}
+
+/// Checks that `envelope` was made against `pp` and that its proof opens
+/// `commitment` to the claimed value at the claimed index. An envelope made
+/// against other parameters is an [`EnvelopeError::SrsMismatch`] rather than
+/// `Invalid`; a value its encoding cannot produce is rejected before any
+/// group operation.
+///
+/// `srs_digest` is `pp.digest()`, taken as an argument so that it is computed
+/// once when checking many envelopes. The rsid list is not available here:
+/// callers holding it should compare [`ProofEnvelope::list_digest`] against
+/// [`list_digest`] themselves.
+pub fn verify_envelope<E: Pairing>(
+    pp: &PublicParameters<E>,
+    srs_digest: &[u8; 32],
+    commitment: &Commitment<E>,
+    envelope: &ProofEnvelope<E>,
+) -> Result<VerifyOutcome, EnvelopeError> {
+    if envelope.srs_digest != *srs_digest {
+        return Err(EnvelopeError::SrsMismatch);
+    }
+    if !in_encoding_range(envelope) {
+        return Ok(VerifyOutcome::Invalid);
+    }
+    envelope
+        .proof
+        .verify(pp, commitment, envelope.index, envelope.value)
+        .map_err(|_| EnvelopeError::IndexOutOfRange)
+}
+
+/// Checks many envelopes against one commitment with a single
+/// [`PointProof::batch_verify`]; an [`EnvelopeError`] if any envelope cannot
+/// be checked. `srs_digest` is `pp.digest()`, as for [`verify_envelope`].
+pub fn batch_verify_envelopes<E: Pairing>(
+    pp: &PublicParameters<E>,
+    srs_digest: &[u8; 32],
+    commitment: &Commitment<E>,
+    envelopes: &[ProofEnvelope<E>],
+    rng: &mut impl RngCore,
+) -> Result<VerifyOutcome, EnvelopeError> {
+    if envelopes
+        .iter()
+        .any(|envelope| envelope.srs_digest != *srs_digest)
+    {
+        return Err(EnvelopeError::SrsMismatch);
+    }
+    if !envelopes.iter().all(in_encoding_range) {
+        return Ok(VerifyOutcome::Invalid);
+    }
+    let openings = envelopes
+        .iter()
+        .map(|envelope| (envelope.index, envelope.value, &envelope.proof))
+        .collect::<Vec<_>>();
+    PointProof::batch_verify(pp, commitment, &openings, rng)
+        .map_err(|_| EnvelopeError::IndexOutOfRange)
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_encoding_range() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 2);
+    let polynomial = (alloc::vec![0, 1], alloc::vec![F::from(2u8), F::from(7u8)]);
+    let commitment = pp.commit_sparse(&polynomial).unwrap();
+    let envelope = |index, value, encoding| {
+        let proof = PointProof::new_sparse(&pp, &polynomial, index).unwrap();
+        ProofEnvelope::new(pp.digest(), proof, index, value, encoding, [0; 32])
+    };
+
+    let valid = envelope(0, F::from(2u8), BASE_ENCODING);
+    assert_eq!(
+        verify_envelope(&pp, &pp.digest(), &commitment, &valid),
+        Ok(VerifyOutcome::Valid)
+    );
+    // a correct opening, but not to a value the base encoding produces
+    let out_of_range = envelope(1, F::from(7u8), BASE_ENCODING);
+    assert_eq!(
+        verify_envelope(&pp, &pp.digest(), &commitment, &out_of_range),
+        Ok(VerifyOutcome::Invalid)
+    );
+    let unknown = envelope(1, F::from(7u8), 42);
+    assert_eq!(
+        verify_envelope(&pp, &pp.digest(), &commitment, &unknown),
+        Ok(VerifyOutcome::Valid)
+    );
+
+    let envelopes = [valid, out_of_range];
+    assert_eq!(
+        batch_verify_envelopes(
+            &pp,
+            &pp.digest(),
+            &commitment,
+            &envelopes,
+            &mut rand::thread_rng()
+        ),
+        Ok(VerifyOutcome::Invalid)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_srs_mismatch() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 2);
+    let other = PublicParameters::<E>::new(&mut rand::thread_rng(), 2);
+    let polynomial = (alloc::vec![0], alloc::vec![F::from(1u8)]);
+    let proof = PointProof::new_sparse(&other, &polynomial, 0).unwrap();
+    let envelope = ProofEnvelope::new(
+        other.digest(),
+        proof,
+        0,
+        F::from(1u8),
+        BASE_ENCODING,
+        [0; 32],
+    );
+
+    let commitment = other.commit_sparse(&polynomial).unwrap();
+    assert_eq!(
+        verify_envelope(&pp, &pp.digest(), &commitment, &envelope),
+        Err(EnvelopeError::SrsMismatch)
+    );
+    assert_eq!(
+        verify_envelope(&other, &other.digest(), &commitment, &envelope),
+        Ok(VerifyOutcome::Valid)
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_envelope_round_trip() {
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 2);
+    let polynomial = (alloc::vec![1], alloc::vec![F::from(2u8)]);
+    let commitment = pp.commit_sparse(&polynomial).unwrap();
+    let proof = PointProof::new_sparse(&pp, &polynomial, 1).unwrap();
+    let envelope = ProofEnvelope::new(
+        pp.digest(),
+        proof,
+        1,
+        F::from(2u8),
+        BASE_ENCODING,
+        list_digest(b"rs1\nrs2\n"),
+    );
+
+    let mut bytes = Vec::new();
+    envelope.serialize_compressed(&mut bytes).unwrap();
+    let decoded = ProofEnvelope::<E>::deserialize_compressed(&bytes[..]).unwrap();
+    assert_eq!(decoded.index(), 1);
+    assert_eq!(decoded.value(), F::from(2u8));
+    assert_eq!(decoded.encoding(), BASE_ENCODING);
+    assert_eq!(decoded.list_digest(), &list_digest(b"rs1\nrs2\n"));
+    assert_eq!(
+        verify_envelope(&pp, &pp.digest(), &commitment, &decoded),
+        Ok(VerifyOutcome::Valid)
+    );
+
+    assert!(is_known_encoding(BASE_ENCODING));
+    assert!(is_known_encoding(ALLELE_HASH_ENCODING));
+    assert!(!is_known_encoding(42));
+}
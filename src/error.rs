@@ -0,0 +1,54 @@
+//! The error type of the VCF parsing, committing and proving API, for callers
+//! that need to tell failures apart, e.g. to map them to HTTP statuses. Details
+//! are also logged where the error is raised.
+use ark_serialize::SerializationError;
+use thiserror::Error;
+
+use crate::commitment::{CommitmentError, VerifyOutcome};
+use crate::dna::RsId;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Built explicitly: `?` on a [`SerializationError`] gives
+    /// [`Error::Deserialization`], by far the more common failure.
+    #[error("serialization error: {0}")]
+    Serialization(SerializationError),
+    #[error("deserialization error: {0}")]
+    Deserialization(#[from] SerializationError),
+    /// An index past the public parameters or the polynomial.
+    #[error("index {0} not found")]
+    IndexNotFound(usize),
+    #[error("verification failed")]
+    VerificationFailed,
+    /// A malformed line of an input file, numbered from 1.
+    #[error("parse error on line {line}")]
+    Parse { line: usize },
+    #[error("{0}")]
+    Commitment(#[from] CommitmentError),
+    #[error("{0} not in the rsid list")]
+    RsIdNotFound(RsId),
+    /// Proving an index absent that holds a value.
+    #[error("index {0} is populated")]
+    IndexPopulated(usize),
+    #[error("unknown value encoding {0}")]
+    UnknownEncoding(u8),
+    /// A claimed value that is neither a decimal nor an allele.
+    #[error("invalid value")]
+    InvalidValue,
+    #[error("unknown chromosome")]
+    UnknownChromosome,
+}
+
+impl VerifyOutcome {
+    /// [`Error::VerificationFailed`] unless the outcome is valid, to use `?` on
+    /// verification results.
+    pub fn check(self) -> Result<(), Error> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(Error::VerificationFailed)
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Headers identifying the pairing curve of serialized artifacts (public
+//! parameters, hashes and proofs), so that mixing curves fails loudly instead
+//! of deserializing into garbage.
+use ark_ec::pairing::Pairing;
+use std::io::{self, BufRead, Write};
+
+/// Marks the start of a header. Artifacts without it predate headers and are
+/// always BLS12-381.
+pub const MAGIC: [u8; 4] = *b"dnap";
+
+pub trait CurveId: Pairing {
+    const CURVE_ID: u8;
+    const NAME: &'static str;
+}
+
+impl CurveId for ark_bls12_381::Bls12_381 {
+    const CURVE_ID: u8 = 1;
+    const NAME: &'static str = "bls12-381";
+}
+
+impl CurveId for ark_bn254::Bn254 {
+    const CURVE_ID: u8 = 2;
+    const NAME: &'static str = "bn254";
+}
+
+/// The name of the curve with id `id`, if it is one of the above.
+pub fn curve_name(id: u8) -> Option<&'static str> {
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+
+    [
+        (Bls12_381::CURVE_ID, Bls12_381::NAME),
+        (Bn254::CURVE_ID, Bn254::NAME),
+    ]
+    .into_iter()
+    .find(|&(curve, _)| curve == id)
+    .map(|(_, name)| name)
+}
+
+pub fn write_curve_id<E: CurveId>(writer: &mut impl Write) -> io::Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&[E::CURVE_ID])
+}
+
+/// Consumes the header, if any, and returns the curve id it declares.
+pub fn read_curve_id(reader: &mut impl BufRead) -> io::Result<u8> {
+    let buf = reader.fill_buf()?;
+    if buf.len() > MAGIC.len() && buf.starts_with(&MAGIC) {
+        let id = buf[MAGIC.len()];
+        reader.consume(MAGIC.len() + 1);
+        Ok(id)
+    } else {
+        Ok(<ark_bls12_381::Bls12_381 as CurveId>::CURVE_ID)
+    }
+}
+
+#[test]
+fn test_curve_id() {
+    type E = ark_bls12_381::Bls12_381;
+
+    let mut bytes = Vec::new();
+    write_curve_id::<E>(&mut bytes).unwrap();
+    bytes.extend_from_slice(b"payload");
+
+    let mut reader = bytes.as_slice();
+    assert_eq!(read_curve_id(&mut reader).unwrap(), E::CURVE_ID);
+    assert_eq!(reader, b"payload");
+
+    let mut legacy = &b"payload"[..];
+    assert_eq!(read_curve_id(&mut legacy).unwrap(), E::CURVE_ID);
+    assert_eq!(legacy, b"payload");
+}
+
+#[test]
+fn test_bn254_curve_id() {
+    type E = ark_bn254::Bn254;
+
+    let mut bytes = Vec::new();
+    write_curve_id::<E>(&mut bytes).unwrap();
+    let id = read_curve_id(&mut bytes.as_slice()).unwrap();
+    assert_ne!(id, <ark_bls12_381::Bls12_381 as CurveId>::CURVE_ID);
+    assert_eq!(curve_name(id), Some("bn254"));
+    assert_eq!(curve_name(0), None);
+}
@@ -0,0 +1,37 @@
+//! Without the default `std` feature only the [`commitment`] module is built,
+//! which is enough to deserialize public parameters and commitments and run
+//! [`PointProof::verify`] in `no_std` + `alloc` environments.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "proof-cache")]
+pub mod cache;
+pub mod commitment;
+#[cfg(feature = "std")]
+pub mod dna;
+pub mod envelope;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
+pub mod header;
+#[cfg(feature = "tokio")]
+pub mod runtime;
+#[cfg(feature = "std")]
+pub mod shard;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use commitment::{
+    BatchPointProof, Blinding, Commitment, CommitmentError, CommitmentG2, MergeProof,
+    PanelBoundProof, PointProof, PointProofG2, PublicParameters, SameValueProof, VerifyOutcome,
+};
+#[cfg(feature = "std")]
+pub use dna::{
+    AggregateDnaProof, CellProof, DnaHash, DnaPoly, GenotypeMatrix, GenotypeMatrixHash,
+    HashBucketFilter, Index, MatchReport, MatchStats, MultiAllelic, QualifiedPointProof,
+    QualifiedRsIdHash, QualifiedRsIdPoly, RsId, RsIdFilter, RsIdHash, RsIdPoly, RsIdPolyBuilder,
+};
+pub use envelope::ProofEnvelope;
+#[cfg(feature = "std")]
+pub use error::Error;
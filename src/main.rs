@@ -1,54 +1,275 @@
-use ark_ec::pairing::Pairing;
-use ark_ff::Field;
+use ark_ff::PrimeField;
 use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use std::{
+    cell::Cell,
     collections::HashMap,
     fs::File,
-    io::{BufRead, BufReader},
-    path::PathBuf,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
 };
 
 use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use rand::rngs::OsRng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 
-mod commitment;
-use commitment::{PointProof, PublicParameters};
-
-mod dna;
-use dna::{RsIdHash, RsIdPoly, base_to_int};
+use dna_proofs::dna::{
+    self, base_to_int, commit_rsids, read_merge_table, read_rsid_list, value_encoding,
+};
+use dna_proofs::envelope::{
+    self, is_known_encoding, list_digest, EnvelopeError, ALLELE_HASH_ENCODING, BASE_ENCODING,
+    DISTINCT_BASE_ENCODING, DOSAGE_ENCODING, MISSING_DOSAGE,
+};
+use dna_proofs::header::{curve_name, read_curve_id, write_curve_id, CurveId};
+use dna_proofs::shard;
+use dna_proofs::{
+    Blinding, Commitment, Error, Index, MatchReport, MatchStats, MultiAllelic, PointProof,
+    ProofEnvelope, PublicParameters, RsId, RsIdHash, RsIdPoly, RsIdPolyBuilder, VerifyOutcome,
+};
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use log::{error, warn};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
-enum Cli {
+struct Cli {
+    /// Number of worker threads, all cores by default
+    #[arg(short, long, global = true)]
+    jobs: Option<usize>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// The vcf to read, and how its rsids are matched to the rsid list.
+#[derive(Args)]
+struct VcfArgs {
+    #[arg(short, long)]
+    vcf: PathBuf,
+    #[arg(long, default_value = "rsidlist")]
+    rsid: PathBuf,
+    /// dbSNP merge table (`rs<old><TAB>rs<new>` lines), to match VCF rsids
+    /// retired since the rsid list was made
+    #[arg(long, value_name = "FILE")]
+    merge_table: Option<PathBuf>,
+    /// How ALT alleles are committed; `base`, the default, is what hashes made
+    /// before the option existed use
+    #[arg(long, value_enum, default_value_t = Encoding::Base)]
+    encoding: Encoding,
+    /// Commit to the number of ALT alleles called in the sample's GT, 0 to 2,
+    /// instead of to the ALT allele; records without a call are skipped
+    #[arg(long, conflicts_with = "encoding")]
+    dosage: bool,
+    /// With `--dosage`, commit a missing call (`./.`) as 3 instead of skipping
+    /// the record
+    #[arg(long, requires = "dosage")]
+    missing_dosage: bool,
+    /// What a multi-allelic record whose genotype calls no ALT allele commits to
+    #[arg(long, value_enum, default_value_t = MultiAllelicMode::First)]
+    multi_allelic: MultiAllelicMode,
+    /// Fix the committed indices to `0..N` whatever the length of the rsid
+    /// list; rsids listed at or past N are dropped
+    #[arg(long, value_name = "N")]
+    pad_to: Option<usize>,
+}
+
+impl VcfArgs {
+    /// The id of what is committed, recorded in envelopes and manifests.
+    fn encoding_id(&self) -> u8 {
+        if self.dosage {
+            DOSAGE_ENCODING
+        } else {
+            self.encoding.id()
+        }
+    }
+}
+
+/// What a multi-allelic record whose genotype calls no ALT allele commits to, see
+/// [`MultiAllelic`].
+#[derive(Clone, Copy, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum MultiAllelicMode {
+    /// The first ALT allele
+    First,
+    /// Nothing, as if the record were absent
+    Skip,
+}
+
+impl MultiAllelicMode {
+    fn builder_mode(self) -> MultiAllelic {
+        match self {
+            MultiAllelicMode::First => MultiAllelic::First,
+            MultiAllelicMode::Skip => MultiAllelic::Skip,
+        }
+    }
+}
+
+/// How alleles are encoded into committed values. Hashes and proofs made under
+/// one encoding do not verify under another.
+#[derive(Clone, Copy, ValueEnum)]
+enum Encoding {
+    /// A and T as 1, C and G as 2, whatever the strand
+    Base,
+    /// A, C, G and T as 1 to 4
+    Distinct,
+    /// Like `distinct`, with indels and structural variants hashed together
+    /// with REF instead of committed as 0; verify their values as `REF>ALT`
+    Allele,
+}
+
+impl Encoding {
+    /// The id recorded in envelopes and manifests.
+    fn id(self) -> u8 {
+        match self {
+            Encoding::Base => BASE_ENCODING,
+            Encoding::Distinct => DISTINCT_BASE_ENCODING,
+            Encoding::Allele => ALLELE_HASH_ENCODING,
+        }
+    }
+}
+
+/// How `hash`, `prove` and `verify` print their result.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Bare hex, or a line for people
+    Text,
+    /// One JSON object, with the index, value, curve and parameter degree
+    Json,
+}
+
+/// The pairing curve of the parameters, hashes and proofs. Each is marked with
+/// its curve, and is rejected under another.
+#[derive(Clone, Copy, ValueEnum)]
+enum Curve {
+    #[value(name = "bls12-381")]
+    Bls12_381,
+    /// Has precompiles on Ethereum, for verifying on chain
+    Bn254,
+}
+
+/// Evaluates `$body` with the type `$E` standing for the pairing of `$curve`.
+macro_rules! with_curve {
+    ($curve:expr, $E:ident => $body:expr) => {
+        match $curve {
+            Curve::Bls12_381 => {
+                type $E = Bls12_381;
+                $body
+            }
+            Curve::Bn254 => {
+                type $E = Bn254;
+                $body
+            }
+        }
+    };
+}
+
+#[derive(Subcommand)]
+enum Command {
     /// Generate parameters
     Init {
         #[arg(short = 'D', default_value_t = 10)]
         degree: usize,
         #[arg(short, long, value_name = "FILE", default_value = "pp.bin")]
         dest: PathBuf,
+        /// Split the parameters across this many files, writing a manifest to
+        /// `dest` and the shards next to it
+        #[arg(long)]
+        shards: Option<usize>,
+        /// Gzip the parameters file; other commands decompress it transparently
+        #[arg(long, conflicts_with = "shards")]
+        gzip: bool,
+        /// Overwrite `dest` if it already exists
+        #[arg(long)]
+        force: bool,
+        /// Derive the parameters from this 32-byte hex seed instead of OS
+        /// entropy; anyone with the seed can forge proofs, so for testing only
+        #[arg(long, value_parser = parse_seed)]
+        seed: Option<[u8; 32]>,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
     },
     /// Commit to a dna
     Hash {
         #[arg(short, long, default_value = "pp.bin")]
         pp: PathBuf,
-        #[arg(short, long)]
-        vcf: PathBuf,
+        #[command(flatten)]
+        vcf: VcfArgs,
+        /// Make a hiding commitment, writing its secret blinding factor to this
+        /// new file; `prove` needs it
+        #[arg(long, value_name = "FILE")]
+        blinding: Option<PathBuf>,
+        /// Report what would be committed and whether it fits the parameters,
+        /// without committing
+        #[arg(long)]
+        dry_run: bool,
+        /// Also write a JSON manifest of the inputs and the hash to this file
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+        /// Also write which rsids of the list were matched or absent, and which
+        /// rsids of the vcf are not in the list, to this JSON file
+        #[arg(long, value_name = "FILE")]
+        report: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
+    },
+    /// Commit to a few variants given on the command line instead of a vcf
+    HashInline {
+        #[arg(short, long, default_value = "pp.bin")]
+        pp: PathBuf,
         #[arg(long, default_value = "rsidlist")]
         rsid: PathBuf,
+        /// A variant as `rs<id>=<alt>`, e.g. `rs123=A`; may be repeated
+        #[arg(long = "snp", value_name = "RSID=ALT", required = true)]
+        snps: Vec<String>,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
     },
     /// Prove a point
     Prove {
-        #[arg(short, long)]
-        vcf: PathBuf,
+        #[command(flatten)]
+        vcf: VcfArgs,
         #[arg(short, long, default_value = "pp.bin")]
         pp: PathBuf,
-        #[arg(long, default_value = "rsidlist")]
-        rsid: PathBuf,
+        /// Blinding factor written by `hash --blinding`, to open a hiding
+        /// commitment
+        #[arg(long, value_name = "FILE")]
+        blinding: Option<PathBuf>,
+        /// Print a self-describing proof envelope instead of the bare proof
+        #[arg(long)]
+        envelope: bool,
+        /// Also write a JSON manifest of the inputs and the proof to this file
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
         // chr: usize,
-        index: usize,
+        /// Number of the rsid to open, e.g. 123 for rs123
+        id: u64,
+    },
+    /// Prove many points at once, one proof per line
+    ProveBatch {
+        #[command(flatten)]
+        vcf: VcfArgs,
+        #[arg(short, long, default_value = "pp.bin")]
+        pp: PathBuf,
+        /// Print proof envelopes, as read by `verify-batch`, instead of bare proofs
+        #[arg(long)]
+        envelope: bool,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
+        /// Numbers of the rsids to open
+        #[arg(required = true)]
+        ids: Vec<u64>,
     },
     Verify {
         #[arg(short, long, default_value = "pp.bin")]
@@ -56,132 +277,1384 @@ enum Cli {
         #[arg(long, default_value = "rsidlist")]
         rsid: PathBuf,
 
-        index: usize,
+        /// Number of the opened rsid, e.g. 123 for rs123
+        id: u64,
         hash: String,
         proof: String,
         value: String,
+        /// How an allele given as the value is encoded
+        #[arg(long, value_enum, default_value_t = Encoding::Base)]
+        encoding: Encoding,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
+    },
+    /// Like `verify`, against a bare commitment instead of a hash
+    VerifyCommitment {
+        #[arg(short, long, default_value = "pp.bin")]
+        pp: PathBuf,
+        #[arg(long, default_value = "rsidlist")]
+        rsid: PathBuf,
+
+        /// Number of the opened rsid, e.g. 123 for rs123
+        id: u64,
+        commitment: String,
+        proof: String,
+        value: String,
+        /// How an allele given as the value is encoded
+        #[arg(long, value_enum, default_value_t = Encoding::Base)]
+        encoding: Encoding,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
     },
+    /// Verify a proof envelope produced by `prove --envelope`
+    VerifyEnvelope {
+        #[arg(short, long, default_value = "pp.bin")]
+        pp: PathBuf,
+        #[arg(long, default_value = "rsidlist")]
+        rsid: PathBuf,
+
+        hash: String,
+        envelope: String,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
+    },
+    /// Verify a directory (one envelope per file) or a file (one per line) of
+    /// proof envelopes against a single hash
+    VerifyBatch {
+        #[arg(short, long, default_value = "pp.bin")]
+        pp: PathBuf,
+        #[arg(long, default_value = "rsidlist")]
+        rsid: PathBuf,
+
+        hash: String,
+        envelopes: PathBuf,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
+    },
+    /// Check whether two hashes commit to the same values, exiting with an error
+    /// if they do not
+    Compare {
+        /// A hex hash, or a file holding one
+        a: String,
+        /// A hex hash, or a file holding one
+        b: String,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
+    },
+    /// Check that a parameters file is sound, telling a truncated file (e.g. from
+    /// an interrupted `init`) from a corrupt one
+    ValidatePp {
+        #[arg(short, long, default_value = "pp.bin")]
+        pp: PathBuf,
+        /// Only check this many random pairs of consecutive powers, a quick
+        /// heuristic rather than a full check
+        #[arg(long, value_name = "N")]
+        sample: Option<usize>,
+        /// Check every pair of consecutive powers with its own pairings instead
+        /// of one randomized check; much slower
+        #[arg(long, conflicts_with = "sample")]
+        full: bool,
+        #[arg(long, value_enum, default_value_t = Curve::Bls12_381)]
+        curve: Curve,
+    },
+    /// Print the index an rsid is assigned in the list
+    Lookup {
+        #[arg(long, default_value = "rsidlist")]
+        rsid: PathBuf,
+
+        id: u64,
+    },
+    /// Answer `POST /verify` over HTTP, with the parameters and the rsid list
+    /// loaded once
+    #[cfg(feature = "server")]
+    Serve {
+        #[arg(short, long, default_value = "pp.bin")]
+        pp: PathBuf,
+        #[arg(long, default_value = "rsidlist")]
+        rsid: PathBuf,
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: std::net::SocketAddr,
+        /// How an allele given as the value is encoded
+        #[arg(long, value_enum, default_value_t = Encoding::Base)]
+        encoding: Encoding,
+    },
+}
+
+const TOO_SMALL: &str =
+    "The rsid list has more entries than the public parameters allow, rerun init with a larger -D";
+
+/// Leading bytes of a gzip stream, see RFC 1952.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Below this fraction of matched rsids `hash` warns about a likely misconfiguration.
+const MIN_MATCH_RATE: f64 = 0.1;
+
+fn open_pp<E: CurveId>(pp_path: PathBuf) -> Result<PublicParameters<E>, &'static str> {
+    let pp_file = std::fs::File::open(&pp_path).map_err(|_| "Error opening pp file")?;
+    let mut pp_file = BufReader::new(pp_file);
+    if shard::is_manifest(&mut pp_file).map_err(|_| "Error reading pp file")? {
+        return shard::read_sharded(&pp_path).map_err(|e| match e {
+            Error::Io(e) if e.kind() != std::io::ErrorKind::InvalidData => "Error opening pp shard",
+            _ => "Invalid or corrupt sharded pp",
+        });
+    }
+    let magic = pp_file.fill_buf().map_err(|_| "Error reading pp file")?;
+    if magic.starts_with(&GZIP_MAGIC) {
+        read_pp(&mut BufReader::new(MultiGzDecoder::new(pp_file)))
+    } else {
+        read_pp(&mut pp_file)
+    }
 }
 
-fn open_pp<E: Pairing>(pp_path: PathBuf) -> Result<PublicParameters<E>, &'static str> {
-    let mut pp_file = std::fs::File::open(pp_path).map_err(|_| "Error opening pp file")?;
-    PublicParameters::<E>::deserialize_compressed_unchecked(&mut pp_file)
+fn read_pp<E: CurveId>(reader: &mut impl BufRead) -> Result<PublicParameters<E>, &'static str> {
+    let curve = read_curve_id(reader).map_err(|_| "Error reading pp file")?;
+    if curve != E::CURVE_ID {
+        if let Some(name) = curve_name(curve) {
+            error!(
+                "The pp file is for {}, not {}; commands taking --curve can select it",
+                name,
+                E::NAME
+            );
+        }
+        return Err("curve mismatch: the pp file was generated for another curve");
+    }
+    PublicParameters::<E>::deserialize_compressed_unchecked(reader)
         .map_err(|_| "Error deserializing")
 }
 
-fn open_vcf<F: Field>(vcf_path: &PathBuf, rsid_path: &PathBuf) -> Result<RsIdPoly<F>, &'static str> {
-    let vcf_file = std::fs::File::open(&vcf_path).map_err(|_| "Error opening vcf file")?;
-    let filter = open_rsid(&rsid_path)?;
+/// Hex-encodes `value` behind a header naming the curve `E`.
+fn encode<E: CurveId>(value: &impl CanonicalSerialize) -> Result<String, &'static str> {
+    let mut output = Vec::new();
+    write_encoded::<E>(&mut HexWriter(&mut output), value)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+/// Writes what [`encode`] returns straight to `writer`, without building the
+/// serialization or the hex in memory.
+fn write_encoded<E: CurveId>(
+    writer: &mut HexWriter<impl Write>,
+    value: &impl CanonicalSerialize,
+) -> Result<(), &'static str> {
+    write_curve_id::<E>(writer).map_err(|_| "Serialization error")?;
+    value
+        .serialize_compressed(writer)
+        .map_err(|_| "Serialization error")
+}
+
+/// Hex-encodes the bytes written to it into the inner writer.
+struct HexWriter<W>(W);
 
-    if vcf_path.ends_with("gz") {
-        Ok(RsIdPoly::<F>::from_file(
-            MultiGzDecoder::new(vcf_file),
-            filter,
-        ))
+impl<W: Write> Write for HexWriter<W> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        let mut hex = [0; 128];
+        for chunk in bytes.chunks(hex.len() / 2) {
+            let hex = &mut hex[..2 * chunk.len()];
+            hex::encode_to_slice(chunk, hex).unwrap();
+            self.0.write_all(hex)?;
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Inverse of [`encode`], rejecting values serialized for another curve.
+fn decode<E: CurveId, T: CanonicalDeserialize>(
+    input: &str,
+    what: &'static str,
+) -> Result<T, &'static str> {
+    let bytes = hex::decode(input).map_err(|_| "Error decoding hex input")?;
+    let mut bytes = bytes.as_slice();
+    let curve = read_curve_id(&mut bytes).map_err(|_| "Error decoding hex input")?;
+    if curve != E::CURVE_ID {
+        return Err("curve mismatch: the input was produced for another curve");
+    }
+    T::deserialize_compressed(&mut bytes).map_err(|_| what)
+}
+
+fn open_vcf<F: PrimeField>(vcf_args: &VcfArgs) -> Result<(RsIdPoly<F>, MatchStats), &'static str> {
+    let builder = vcf_builder(vcf_args)?;
+    read_vcf_file(&vcf_args.vcf, |vcf| builder.from_file(vcf))
+}
+
+fn vcf_builder<F: PrimeField>(vcf_args: &VcfArgs) -> Result<RsIdPolyBuilder<F>, &'static str> {
+    let filter = open_rsid(&vcf_args.rsid)?;
+    let builder = match vcf_args.encoding_id() {
+        DOSAGE_ENCODING => RsIdPolyBuilder::<F>::new(filter)
+            .dosage(vcf_args.missing_dosage.then_some(MISSING_DOSAGE)),
+        ALLELE_HASH_ENCODING => RsIdPolyBuilder::<F>::new(filter).allele_hash(),
+        encoding => {
+            let encoding = value_encoding(encoding).ok_or("Unknown value encoding")?;
+            RsIdPolyBuilder::<F>::with_encoding(filter, encoding)
+        }
+    };
+    let builder = builder
+        .multi_allelic(vcf_args.multi_allelic.builder_mode())
+        .pad_to(vcf_args.pad_to);
+    match &vcf_args.merge_table {
+        Some(merge_table) => {
+            let table = File::open(merge_table).map_err(|_| "Error opening merge table")?;
+            let merges = read_merge_table(table).map_err(|_| "Invalid merge table")?;
+            Ok(builder.merge_table(merges))
+        }
+        None => Ok(builder),
+    }
+}
+
+/// Opens a VCF and hands it to `parse`, decompressing it if it has a `.gz` or
+/// `.bgz` extension or starts with the gzip magic bytes.
+fn read_vcf_file<T>(
+    vcf_path: &Path,
+    parse: impl FnOnce(Box<dyn Read>) -> T,
+) -> Result<T, &'static str> {
+    let vcf_file = File::open(vcf_path).map_err(|_| "Error opening vcf file")?;
+    let mut vcf_file = BufReader::new(vcf_file);
+    let gzipped = match vcf_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        Some("gz" | "bgz") => true,
+        _ => vcf_file
+            .fill_buf()
+            .map_err(|_| "Error reading vcf file")?
+            .starts_with(&[0x1f, 0x8b]),
+    };
+    if !gzipped {
+        return Ok(parse(Box::new(vcf_file)));
+    }
+
+    let failed = Rc::new(Cell::new(false));
+    let parsed = parse(Box::new(GzVcf {
+        decoder: MultiGzDecoder::new(vcf_file),
+        failed: failed.clone(),
+    }));
+    if failed.get() {
+        Err("Error decompressing vcf file")
     } else {
-        Ok(RsIdPoly::<F>::from_file(vcf_file, filter))
+        Ok(parsed)
+    }
+}
+
+/// A gzipped VCF that ends at the first decompression error, recording it in
+/// `failed`: the parser would otherwise take a corrupt stream for a short VCF,
+/// or abort on it.
+struct GzVcf<R> {
+    decoder: MultiGzDecoder<R>,
+    failed: Rc<Cell<bool>>,
+}
+
+impl<R: Read> Read for GzVcf<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.decoder.read(buf).or_else(|_| {
+            self.failed.set(true);
+            Ok(0)
+        })
+    }
+}
+
+/// Writes which rsids were committed, as JSON lists of `rs<id>` strings.
+fn write_report(report: &MatchReport, path: &Path) -> Result<(), &'static str> {
+    let names = |rsids: &[RsId]| rsids.iter().map(RsId::to_string).collect::<Vec<_>>();
+    let json = serde_json::json!({
+        "matched": names(&report.matched),
+        "absent": names(&report.absent),
+        "unlisted": names(&report.unlisted),
+    });
+    let file = File::create(path).map_err(|_| "Error creating report file")?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, &json).map_err(|_| "Error writing report")?;
+    writer.flush().map_err(|_| "Error writing report")
+}
+
+/// The exact inputs and output of a `hash` or `prove` run, written with
+/// `--manifest` so that the run can be reproduced and its output attributed.
+#[derive(Serialize)]
+struct RunManifest {
+    tool_version: &'static str,
+    curve: u8,
+    encoding: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    missing_dosage: Option<u8>,
+    multi_allelic: MultiAllelicMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pad_to: Option<usize>,
+    srs_sha256: String,
+    rsid_list_sha256: String,
+    vcf_sha256: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rsid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<usize>,
+    /// The hex hash or proof, as printed.
+    output: String,
+}
+
+impl RunManifest {
+    fn new<E: CurveId>(
+        pp: &PublicParameters<E>,
+        vcf_args: &VcfArgs,
+        output: String,
+    ) -> Result<Self, &'static str> {
+        let list = std::fs::read(&vcf_args.rsid).map_err(|_| "Error opening rsid list")?;
+        let mut vcf = File::open(&vcf_args.vcf).map_err(|_| "Error opening vcf file")?;
+        let mut vcf_digest = Sha256::new();
+        std::io::copy(&mut vcf, &mut vcf_digest).map_err(|_| "Error reading vcf file")?;
+        Ok(Self {
+            tool_version: env!("CARGO_PKG_VERSION"),
+            curve: E::CURVE_ID,
+            encoding: vcf_args.encoding_id(),
+            missing_dosage: (vcf_args.dosage && vcf_args.missing_dosage).then_some(MISSING_DOSAGE),
+            multi_allelic: vcf_args.multi_allelic,
+            pad_to: vcf_args.pad_to,
+            srs_sha256: hex::encode(pp.digest()),
+            rsid_list_sha256: hex::encode(list_digest(&list)),
+            vcf_sha256: hex::encode(vcf_digest.finalize()),
+            rsid: None,
+            index: None,
+            output,
+        })
+    }
+
+    fn write(&self, path: &Path) -> Result<(), &'static str> {
+        let file = File::create(path).map_err(|_| "Error creating manifest file")?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, self).map_err(|_| "Error writing manifest")?;
+        writer.flush().map_err(|_| "Error writing manifest")
     }
 }
 
-fn open_rsid(rsid_path: &PathBuf) -> Result<HashMap<usize, usize>, &'static str> {
+fn open_rsid(rsid_path: &PathBuf) -> Result<HashMap<RsId, Index>, &'static str> {
     let rsid_file = File::open(rsid_path).map_err(|_| "Error opening rsid list")?;
-    Ok(BufReader::new(rsid_file)
-        .lines()
-        .enumerate()
-        .map(|(x, y)| (y.unwrap()[2..].parse().unwrap(), x))
-        .collect())
+    let filter = read_rsid_list(rsid_file).map_err(|_| "Invalid rsid list")?;
+    if filter.is_empty() {
+        return Err("The rsid list is empty");
+    }
+    Ok(filter)
 }
 
-fn setup(dest: PathBuf, degree: usize) -> Result<(), &'static str> {
-    let pp = PublicParameters::<ark_bls12_381::Bls12_381>::new(&mut OsRng, degree);
-    let mut file = std::fs::File::create(dest).unwrap();
-    CanonicalSerialize::serialize_compressed(&pp, &mut file).map_err(|_| "Serialization error")
+fn setup<E: CurveId>(
+    dest: PathBuf,
+    degree: usize,
+    shards: Option<usize>,
+    gzip: bool,
+    force: bool,
+    seed: Option<[u8; 32]>,
+) -> Result<(), &'static str> {
+    let generate = || match seed {
+        Some(seed) => PublicParameters::<E>::from_seed(seed, degree),
+        None => PublicParameters::<E>::new(&mut OsRng, degree),
+    };
+    const EXISTS: &str = "The pp file already exists, pass --force to overwrite it";
+    if let Some(shards) = shards {
+        if dest.exists() && !force {
+            return Err(EXISTS);
+        }
+        let pp = generate();
+        return shard::write_sharded(&pp, &dest, shards).map_err(|_| "Error writing pp shards");
+    }
+    // Open before generating, so that a refusal costs nothing.
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .create_new(!force)
+        .truncate(true)
+        .open(&dest)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => EXISTS,
+            _ => "Error creating pp file",
+        })?;
+    let pp = generate();
+    let mut file = BufWriter::new(file);
+    if gzip {
+        let mut file = GzEncoder::new(file, Compression::default());
+        write_pp(&pp, &mut file)?;
+        file.finish()
+            .and_then(|mut file| file.flush())
+            .map_err(|_| "Error writing pp file")
+    } else {
+        write_pp(&pp, &mut file)?;
+        file.flush().map_err(|_| "Error writing pp file")
+    }
 }
 
-fn hash(pp_path: PathBuf, vcf_path: PathBuf, rsid_path: PathBuf) -> Result<(), &'static str> {
-    let pp = open_pp::<Bls12_381>(pp_path).map_err(|_| "Deserialization error")?;
-    let vcf = open_vcf(&vcf_path, &rsid_path)?;
+/// Reads a `--seed` given as 64 hex digits.
+fn parse_seed(seed: &str) -> Result<[u8; 32], &'static str> {
+    let mut bytes = [0; 32];
+    hex::decode_to_slice(seed, &mut bytes).map_err(|_| "The seed must be 64 hex digits")?;
+    Ok(bytes)
+}
 
-    let mut output = Vec::new();
-    let hash = RsIdHash::new(&pp, &vcf);
-    hash.serialize_compressed(&mut output)
-        .map_err(|_| "Serialization error")?;
+fn write_pp<E: CurveId>(
+    pp: &PublicParameters<E>,
+    writer: &mut impl Write,
+) -> Result<(), &'static str> {
+    write_curve_id::<E>(writer).map_err(|_| "Serialization error")?;
+    pp.serialize_compressed(writer)
+        .map_err(|_| "Serialization error")
+}
+
+fn hash<E: CurveId>(
+    pp_path: PathBuf,
+    vcf_args: VcfArgs,
+    blinding: Option<PathBuf>,
+    dry_run: bool,
+    manifest: Option<PathBuf>,
+    report: Option<PathBuf>,
+    format: Format,
+) -> Result<(), &'static str> {
+    let pp = open_pp::<E>(pp_path)?;
+    let (vcf, stats) = match report {
+        Some(report) => {
+            let builder = vcf_builder(&vcf_args)?;
+            let (vcf, stats, matches) =
+                read_vcf_file(&vcf_args.vcf, |vcf| builder.from_file_with_report(vcf))?;
+            write_report(&matches, &report)?;
+            (vcf, stats)
+        }
+        None => open_vcf(&vcf_args)?,
+    };
 
-    println!("{}", hex::encode(output));
+    if dry_run {
+        println!("matched: {} of {} rsids", stats.matched, stats.total);
+        if stats.dropped > 0 {
+            println!("dropped: {} matches past --pad-to", stats.dropped);
+        }
+        println!("srs capacity: {} indices", pp.max_degree() + 1);
+        match vcf.max_index() {
+            Some(max_index) if max_index.0 > pp.max_degree() => {
+                println!(
+                    "max index: {} (does not fit, rerun init with a larger -D)",
+                    max_index
+                )
+            }
+            Some(max_index) => println!("max index: {}", max_index),
+            None => println!("max index: none"),
+        }
+        return Ok(());
+    }
+
+    if stats.matched == 0 {
+        return Err("No rsid from the list was found in the vcf");
+    }
+    if stats.rate() < MIN_MATCH_RATE {
+        warn!(
+            "Only {} of {} rsids in the list were found in the vcf; \
+             check that both use the same reference build",
+            stats.matched, stats.total
+        );
+    }
+
+    let hiding = blinding.is_some();
+    let hash = match blinding {
+        Some(blinding_path) => {
+            let (hash, blinding) =
+                RsIdHash::new_hiding(&pp, &vcf, &mut OsRng).map_err(|_| TOO_SMALL)?;
+            write_blinding(&encode::<E>(&blinding)?, &blinding_path)?;
+            hash
+        }
+        None => RsIdHash::new(&pp, &vcf).map_err(|_| TOO_SMALL)?,
+    };
+    let hash = encode::<E>(&hash)?;
+    if let Some(manifest) = manifest {
+        RunManifest::new(&pp, &vcf_args, hash.clone())?.write(&manifest)?;
+    }
+    match format {
+        Format::Text => println!("{}", hash),
+        Format::Json => println!(
+            "{}",
+            serde_json::json!({
+                "commitment": hash,
+                "hiding": hiding,
+                "matched": stats.matched,
+                "total": stats.total,
+                "dropped": stats.dropped,
+                "curve": E::NAME,
+                "max_degree": pp.max_degree(),
+            })
+        ),
+    }
     Ok(())
 }
 
-fn prove(
+/// Writes the hex blinding factor of a hiding hash to a new file, never over an
+/// older one: that would lose the factor the older hash needs for proving.
+fn write_blinding(blinding: &str, path: &Path) -> Result<(), &'static str> {
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => "The blinding file already exists",
+            _ => "Error creating blinding file",
+        })?;
+    writeln!(file, "{}", blinding).map_err(|_| "Error writing blinding file")
+}
+
+fn hash_inline<E: CurveId>(
     pp_path: PathBuf,
-    vcf_path: PathBuf,
-    index: usize,
     rsid_path: PathBuf,
+    snps: Vec<String>,
+) -> Result<(), &'static str> {
+    let pp = open_pp::<E>(pp_path)?;
+    let filter = open_rsid(&rsid_path)?;
+
+    // the same encoding a vcf's ALT column goes through
+    let genotypes = snps
+        .iter()
+        .map(|snp| {
+            let (rsid, alt) = snp.split_once('=').ok_or("Expected --snp rs<id>=<alt>")?;
+            let rsid = rsid.parse::<RsId>().map_err(|_| "Invalid rsid in --snp")?;
+            Ok((rsid, base_to_int(alt.as_bytes())))
+        })
+        .collect::<Result<Vec<_>, &'static str>>()?;
+    println!(
+        "{}",
+        encode::<E>(&commit_rsids(&pp, &filter, &genotypes).map_err(|_| TOO_SMALL)?)?
+    );
+    Ok(())
+}
+
+fn prove<E: CurveId>(
+    pp_path: PathBuf,
+    vcf_args: VcfArgs,
+    rsid: RsId,
+    blinding: Option<PathBuf>,
+    envelope: bool,
+    manifest: Option<PathBuf>,
+    format: Format,
 ) -> Result<(), &'static str> {
     let pp = open_pp(pp_path)?;
-    let vcf = open_vcf(&vcf_path, &rsid_path)?;
+    let (vcf, _) = open_vcf(&vcf_args)?;
 
-    let filter = open_rsid(&rsid_path)?;
-    let index = *filter.get(&index).ok_or("index not found")?;
+    let filter = open_rsid(&vcf_args.rsid)?;
+    let index = *filter.get(&rsid).ok_or("rsid not in the list")?;
+
+    let blinding = blinding
+        .map(|blinding| {
+            let blinding =
+                std::fs::read_to_string(blinding).map_err(|_| "Error reading blinding")?;
+            decode::<E, Blinding<E>>(blinding.trim(), "Error deserializing blinding")
+        })
+        .transpose()?;
+    let proof = match &blinding {
+        Some(blinding) => RsIdHash::prove_hiding(&pp, &vcf, index, blinding, &mut OsRng),
+        None => RsIdHash::<E>::prove(&pp, &vcf, index),
+    }
+    .map_err(|_| TOO_SMALL)?;
 
+    let encoded_proof = encode::<E>(&proof)?;
+    let envelope = if envelope {
+        let list = std::fs::read(&vcf_args.rsid).map_err(|_| "Error opening rsid list")?;
+        let envelope = ProofEnvelope::new(
+            pp.digest(),
+            proof,
+            index.0,
+            vcf.value_at(index),
+            vcf_args.encoding_id(),
+            list_digest(&list),
+        );
+        Some(encode::<E>(&envelope)?)
+    } else {
+        None
+    };
+    let output = envelope.clone().unwrap_or_else(|| encoded_proof.clone());
+    if let Some(manifest) = manifest {
+        RunManifest {
+            rsid: Some(rsid.to_string()),
+            index: Some(index.0),
+            ..RunManifest::new(&pp, &vcf_args, output.clone())?
+        }
+        .write(&manifest)?;
+    }
+    match format {
+        Format::Text => println!("{}", output),
+        Format::Json => {
+            // the commitment the proof opens, hiding if made with `--blinding`
+            let hash = match &blinding {
+                Some(blinding) => RsIdHash::with_blinding(&pp, &vcf, blinding),
+                None => RsIdHash::new(&pp, &vcf),
+            }
+            .map_err(|_| TOO_SMALL)?;
+            println!(
+                "{}",
+                serde_json::json!({
+                    "rsid": rsid.to_string(),
+                    "index": index.0,
+                    "value": vcf.value_at(index).to_string(),
+                    "proof": encoded_proof,
+                    "envelope": envelope,
+                    "commitment": encode::<E>(&hash)?,
+                    "curve": E::NAME,
+                    "max_degree": pp.max_degree(),
+                })
+            )
+        }
+    }
 
-    let proof = RsIdHash::<Bls12_381>::prove(&pp, &vcf, index).unwrap();
+    Ok(())
+}
 
-    let mut output = Vec::new();
-    proof
-        .serialize_compressed(&mut output)
-        .map_err(|_| "Serialization error")?;
-    println!("{}", hex::encode(&output));
+/// Opens every rsid of `rsids`, streaming one hex proof (or envelope) per line
+/// to stdout as `verify-batch` reads them back.
+fn prove_batch<E: CurveId>(
+    pp_path: PathBuf,
+    vcf_args: VcfArgs,
+    rsids: Vec<RsId>,
+    envelope: bool,
+) -> Result<(), &'static str> {
+    let pp = open_pp(pp_path)?;
+    let (vcf, _) = open_vcf(&vcf_args)?;
+
+    let filter = open_rsid(&vcf_args.rsid)?;
+    let indices = rsids
+        .iter()
+        .map(|rsid| filter.get(rsid).copied().ok_or("rsid not in the list"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let proofs =
+        RsIdHash::<E>::prove_many(&pp, &vcf, &indices).map_err(|_| "Error computing the proofs")?;
+    let list = std::fs::read(&vcf_args.rsid).map_err(|_| "Error opening rsid list")?;
+    let digest = list_digest(&list);
+    let srs_digest = pp.digest();
+    let encoding = vcf_args.encoding_id();
+
+    let mut writer = HexWriter(BufWriter::new(std::io::stdout().lock()));
+    for (proof, index) in proofs.into_iter().zip(indices) {
+        if envelope {
+            let value = vcf.value_at(index);
+            let envelope = ProofEnvelope::new(srs_digest, proof, index.0, value, encoding, digest);
+            write_encoded::<E>(&mut writer, &envelope)?;
+        } else {
+            write_encoded::<E>(&mut writer, &proof)?;
+        }
+        // framing goes around the hex encoding
+        writer
+            .0
+            .write_all(b"\n")
+            .map_err(|_| "Error writing proofs")?;
+    }
+    writer.flush().map_err(|_| "Error writing proofs")
+}
 
+/// Reads a claimed value, see [`dna::parse_value`].
+fn parse_value<F: PrimeField>(value: &str, encoding: Encoding) -> Result<F, &'static str> {
+    dna::parse_value(value, encoding.id()).map_err(|_| "The value must be below the field modulus")
+}
+
+fn verify<E: CurveId>(
+    pp_path: PathBuf,
+    hash: String,
+    proof: String,
+    rsid: RsId,
+    value: E::ScalarField,
+    rsid_path: PathBuf,
+    format: Format,
+) -> Result<(), &'static str> {
+    let hash: RsIdHash<E> = decode::<E, _>(&hash, "Error deserializing hash")?;
+    verify_opening(
+        pp_path,
+        hash.commitment(),
+        proof,
+        rsid,
+        value,
+        rsid_path,
+        format,
+    )
+}
+
+fn verify_commitment<E: CurveId>(
+    pp_path: PathBuf,
+    commitment: String,
+    proof: String,
+    rsid: RsId,
+    value: E::ScalarField,
+    rsid_path: PathBuf,
+    format: Format,
+) -> Result<(), &'static str> {
+    let commitment: Commitment<E> = decode::<E, _>(&commitment, "Error deserializing commitment")?;
+    verify_opening(pp_path, commitment, proof, rsid, value, rsid_path, format)
+}
+
+fn verify_opening<E: CurveId>(
+    pp_path: PathBuf,
+    commitment: Commitment<E>,
+    proof: String,
+    rsid: RsId,
+    value: E::ScalarField,
+    rsid_path: PathBuf,
+    format: Format,
+) -> Result<(), &'static str> {
+    let pp = open_pp(pp_path)?;
+    let filter = open_rsid(&rsid_path)?;
+    let (index, outcome) = check_opening(&pp, &filter, &commitment, &proof, rsid, value)?;
+    match format {
+        Format::Text => {
+            check_outcome(outcome)?;
+            report_opening(rsid, value);
+        }
+        Format::Json => {
+            println!(
+                "{}",
+                serde_json::json!({
+                    "rsid": rsid.to_string(),
+                    "index": index.0,
+                    "value": value.to_string(),
+                    "valid": outcome.is_valid(),
+                    "curve": E::NAME,
+                    "max_degree": pp.max_degree(),
+                })
+            );
+            check_outcome(outcome)?;
+        }
+    }
     Ok(())
 }
 
-fn verify(
+/// Checks the hex `proof` that `commitment` opens to `value` at `rsid`.
+fn check_opening<E: CurveId>(
+    pp: &PublicParameters<E>,
+    filter: &HashMap<RsId, Index>,
+    commitment: &Commitment<E>,
+    proof: &str,
+    rsid: RsId,
+    value: E::ScalarField,
+) -> Result<(Index, VerifyOutcome), &'static str> {
+    let index = *filter.get(&rsid).ok_or("rsid not in the list")?;
+    let proof: PointProof<E> = decode::<E, _>(proof, "Error deserializing proof")?;
+    let outcome = proof
+        .verify(pp, commitment, index.0, value)
+        .map_err(|_| "Index outside the public parameters")?;
+    Ok((index, outcome))
+}
+
+fn verify_envelope<E: CurveId>(
     pp_path: PathBuf,
     hash: String,
     proof: String,
-    index: usize,
-    value: usize,
     rsid_path: PathBuf,
 ) -> Result<(), &'static str> {
     let pp = open_pp(pp_path)?;
+    let list = std::fs::read(&rsid_path).map_err(|_| "Error opening rsid list")?;
+
+    let hash: RsIdHash<E> = decode::<E, _>(&hash, "Error deserializing hash")?;
+    let proof: ProofEnvelope<E> = decode::<E, _>(&proof, "Error deserializing envelope")?;
+
+    if !is_known_encoding(proof.encoding()) {
+        return Err("Unknown value encoding");
+    }
+    if *proof.list_digest() != list_digest(&list) {
+        return Err("The proof was made against a different rsid list");
+    }
+    let outcome = envelope::verify_envelope(&pp, &pp.digest(), &hash.commitment(), &proof)
+        .map_err(envelope_error)?;
+    check_outcome(outcome)?;
 
     let filter = open_rsid(&rsid_path)?;
-    let index = *filter.get(&index).ok_or("index not found")?;
+    match filter.iter().find(|(_, index)| index.0 == proof.index()) {
+        Some((rsid, _)) => report_opening(rsid, proof.value()),
+        None => report_opening(format!("index {}", proof.index()), proof.value()),
+    }
+    Ok(())
+}
+
+/// Spells out what a valid opening means, since 0 is also what an rsid absent
+/// from the vcf commits to.
+fn report_opening<F: PrimeField>(subject: impl std::fmt::Display, value: F) {
+    if value.is_zero() {
+        println!("VALID: {} opens to 0 (absent or reference)", subject);
+    } else {
+        println!("VALID: {} opens to {}", subject, value);
+    }
+}
+
+/// Reads a hex hash given inline or as the path of a file holding it.
+fn read_hash<E: CurveId>(hash: &str) -> Result<RsIdHash<E>, &'static str> {
+    let path = Path::new(hash);
+    if path.is_file() {
+        let contents = std::fs::read_to_string(path).map_err(|_| "Error reading hash file")?;
+        decode::<E, _>(contents.trim(), "Error deserializing hash")
+    } else {
+        decode::<E, _>(hash, "Error deserializing hash")
+    }
+}
+
+fn compare<E: CurveId>(a: String, b: String) -> Result<(), &'static str> {
+    // compare the decoded points, not the hex
+    if read_hash::<E>(&a)?.commitment() == read_hash::<E>(&b)?.commitment() {
+        println!("equal");
+        Ok(())
+    } else {
+        println!("different");
+        Err("The hashes commit to different values")
+    }
+}
+
+fn envelope_error(error: EnvelopeError) -> &'static str {
+    match error {
+        EnvelopeError::SrsMismatch => {
+            "srs mismatch: the proof was made against different public parameters"
+        }
+        EnvelopeError::IndexOutOfRange => "Index outside the public parameters",
+    }
+}
+
+fn check_outcome(outcome: VerifyOutcome) -> Result<(), &'static str> {
+    match outcome {
+        VerifyOutcome::Valid => Ok(()),
+        VerifyOutcome::Invalid => Err("Verification error"),
+    }
+}
+
+/// Reads hex envelopes from every file of a directory, or from every line of a
+/// file; lines may also be JSON, either a string or the output of
+/// `prove --format json --envelope`.
+fn read_envelopes(path: &PathBuf) -> Result<Vec<String>, &'static str> {
+    let contents = if path.is_dir() {
+        let mut files = std::fs::read_dir(path)
+            .map_err(|_| "Error reading envelope directory")?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| "Error reading envelope directory")?;
+        files.sort();
+        files
+            .iter()
+            .map(std::fs::read_to_string)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| "Error reading envelope file")?
+    } else {
+        let contents = std::fs::read_to_string(path).map_err(|_| "Error reading envelope file")?;
+        contents.lines().map(String::from).collect()
+    };
+
+    contents
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(parse_envelope_line)
+        .collect()
+}
+
+fn parse_envelope_line(line: &str) -> Result<String, &'static str> {
+    if !line.starts_with(['"', '{']) {
+        return Ok(line.to_string());
+    }
+    let json = serde_json::from_str::<serde_json::Value>(line)
+        .map_err(|_| "Error parsing JSON envelope")?;
+    match json {
+        serde_json::Value::String(envelope) => Ok(envelope),
+        serde_json::Value::Object(mut fields) => match fields.remove("envelope") {
+            Some(serde_json::Value::String(envelope)) => Ok(envelope),
+            _ => Err("JSON line without an envelope"),
+        },
+        _ => Err("Error parsing JSON envelope"),
+    }
+}
+
+fn verify_batch<E: CurveId>(
+    pp_path: PathBuf,
+    hash: String,
+    envelopes_path: PathBuf,
+    rsid_path: PathBuf,
+) -> Result<(), &'static str> {
+    let pp = open_pp(pp_path)?;
+    let list = std::fs::read(&rsid_path).map_err(|_| "Error opening rsid list")?;
+    let hash: RsIdHash<E> = decode::<E, _>(&hash, "Error deserializing hash")?;
+    let commitment = hash.commitment();
+    let srs_digest = pp.digest();
+
+    let envelopes = read_envelopes(&envelopes_path)?
+        .iter()
+        .map(|envelope| decode::<E, ProofEnvelope<_>>(envelope, "Error deserializing envelope"))
+        .collect::<Result<Vec<_>, _>>()?;
+    if envelopes
+        .iter()
+        .any(|envelope| !is_known_encoding(envelope.encoding()))
+    {
+        return Err("Unknown value encoding");
+    }
+    let digest = list_digest(&list);
+    if envelopes
+        .iter()
+        .any(|envelope| *envelope.list_digest() != digest)
+    {
+        return Err("Some proofs were made against a different rsid list");
+    }
 
-    let hash = hex::decode(hash).map_err(|_| "Error decoding hash")?;
-    let hash = RsIdHash::<Bls12_381>::deserialize_compressed(&mut hash.as_slice())
-        .map_err(|_| "Error deserializing hash")?;
+    let outcome =
+        envelope::batch_verify_envelopes(&pp, &srs_digest, &commitment, &envelopes, &mut OsRng)
+            .map_err(envelope_error)?;
+    if outcome.is_valid() {
+        println!("{} of {} proofs verified", envelopes.len(), envelopes.len());
+        return Ok(());
+    }
 
-    let proof = hex::decode(proof).map_err(|_| "Error decoding proof")?;
-    let proof = PointProof::<Bls12_381>::deserialize_compressed(&mut proof.as_slice())
-        .map_err(|_| "Error deserializing proof")?;
+    // the batch check only says that something failed, find out what
+    let mut failed = 0;
+    for envelope in &envelopes {
+        if envelope::verify_envelope(&pp, &srs_digest, &commitment, envelope)
+            != Ok(VerifyOutcome::Valid)
+        {
+            println!("FAIL index {}", envelope.index());
+            failed += 1;
+        }
+    }
+    println!(
+        "{} of {} proofs verified",
+        envelopes.len() - failed,
+        envelopes.len()
+    );
+    Err("Some proofs failed verification")
+}
 
-    proof
-        .verify(&pp, &hash.into(), index, ark_bls12_381::Fr::from(value as i8))
-        .map_err(|_| "Verification error")?;
+fn validate_pp<E: CurveId>(
+    pp_path: PathBuf,
+    sample: Option<usize>,
+    full: bool,
+) -> Result<(), &'static str> {
+    let pp_file = File::open(&pp_path).map_err(|_| "Error opening pp file")?;
+    let mut pp_file = BufReader::new(pp_file);
+    let pp = if shard::is_manifest(&mut pp_file).map_err(|_| "Error reading pp file")? {
+        open_pp::<E>(pp_path)?
+    } else {
+        let mut bytes = Vec::new();
+        let magic = pp_file.fill_buf().map_err(|_| "Error reading pp file")?;
+        if magic.starts_with(&GZIP_MAGIC) {
+            // a truncated gzip stream still yields what was written before the cut
+            let _ = MultiGzDecoder::new(pp_file).read_to_end(&mut bytes);
+        } else {
+            pp_file
+                .read_to_end(&mut bytes)
+                .map_err(|_| "Error reading pp file")?;
+        }
+        let mut reader = bytes.as_slice();
+        if read_curve_id(&mut reader).map_err(|_| "Error reading pp file")? != E::CURVE_ID {
+            return Err("curve mismatch: the pp file was generated for another curve");
+        }
+        match PublicParameters::<E>::deserialize_compressed_unchecked(reader) {
+            Ok(pp) => pp,
+            Err(_) => {
+                report_truncation::<E>(reader);
+                return Err("Invalid pp file");
+            }
+        }
+    };
+
+    if let Some(samples) = sample {
+        return match pp.verify_sampled(samples, &mut OsRng) {
+            Ok(()) => {
+                println!("ok (sampled): max degree {}", pp.max_degree());
+                Ok(())
+            }
+            Err(index) => {
+                println!("corrupt: power {} is not tau times the previous one", index);
+                Err("Invalid pp file")
+            }
+        };
+    }
+    let verified = if full { pp.verify_full() } else { pp.verify() };
+    match verified.map(|()| pp.verify_g2()) {
+        Ok(Ok(())) => {
+            println!("ok: max degree {}", pp.max_degree());
+            Ok(())
+        }
+        Ok(Err(index)) => {
+            println!(
+                "corrupt: G2 power {} is not tau times the previous one",
+                index
+            );
+            Err("Invalid pp file")
+        }
+        Err(0) => {
+            println!("corrupt: no power of tau is usable, rerun init");
+            Err("Invalid pp file")
+        }
+        Err(index) => {
+            println!(
+                "corrupt: power {} is not tau times the previous one; \
+                 powers up to degree {} are sound",
+                index,
+                index - 1
+            );
+            Err("Invalid pp file")
+        }
+    }
+}
+
+/// Explains why `bytes`, the parameters without their header, do not
+/// deserialize: the G1 powers come first, prefixed by their count, then the G2
+/// powers.
+fn report_truncation<E: Pairing>(bytes: &[u8]) {
+    let point_size = E::G1Affine::default().compressed_size();
+    if bytes.len() < 8 {
+        println!("truncated: the file holds no powers of tau, rerun init");
+        return;
+    }
+    let (len, powers) = bytes.split_at(8);
+    let len = u64::from_le_bytes(len.try_into().unwrap()) as usize;
+    let available = powers.len() / point_size;
+    if available < len {
+        println!(
+            "truncated: {} of {} powers of tau in G1 present (up to degree {}), \
+             but the G2 powers stored after them are lost; rerun init",
+            available,
+            len,
+            available.saturating_sub(1)
+        );
+    } else {
+        println!("corrupt: the G1 powers of tau are all present but the file does not decode, rerun init");
+    }
+}
+
+fn lookup(rsid_path: PathBuf, rsid: RsId) -> Result<(), &'static str> {
+    let filter = open_rsid(&rsid_path)?;
+    match filter.get(&rsid) {
+        Some(index) => println!("{}: index {}", rsid, index),
+        None => println!("{}: not in list", rsid),
+    }
     Ok(())
 }
 
+/// What `serve` loads once and shares between requests.
+#[cfg(feature = "server")]
+struct Verifier {
+    pp: PublicParameters<Bls12_381>,
+    filter: HashMap<RsId, Index>,
+    encoding: Encoding,
+}
+
+/// The body of `POST /verify`: the arguments of `verify`, with the opened
+/// variant given either as its rsid, `rs<id>`, or as its index in the list.
+#[cfg(feature = "server")]
+#[derive(serde::Deserialize)]
+struct VerifyRequest {
+    rsid: Option<String>,
+    index: Option<usize>,
+    hash: String,
+    proof: String,
+    value: String,
+}
+
+#[cfg(feature = "server")]
+impl Verifier {
+    /// The outcome and what `verify --format json` prints for `request`.
+    fn verify(
+        &self,
+        request: &VerifyRequest,
+    ) -> Result<(VerifyOutcome, serde_json::Value), &'static str> {
+        let hash: RsIdHash<Bls12_381> =
+            decode::<Bls12_381, _>(&request.hash, "Error deserializing hash")?;
+        let value = parse_value(&request.value, self.encoding)?;
+        let (rsid, index, outcome) = match (&request.rsid, request.index) {
+            (Some(rsid), None) => {
+                let rsid: RsId = rsid.parse().map_err(|_| "Invalid rsid")?;
+                let (index, outcome) = check_opening(
+                    &self.pp,
+                    &self.filter,
+                    &hash.commitment(),
+                    &request.proof,
+                    rsid,
+                    value,
+                )?;
+                (Some(rsid), index, outcome)
+            }
+            (None, Some(index)) => {
+                let proof: PointProof<Bls12_381> =
+                    decode::<Bls12_381, _>(&request.proof, "Error deserializing proof")?;
+                let outcome = proof
+                    .verify(&self.pp, &hash.commitment(), index, value)
+                    .map_err(|_| "Index outside the public parameters")?;
+                let rsid = self
+                    .filter
+                    .iter()
+                    .find(|(_, other)| other.0 == index)
+                    .map(|(rsid, _)| *rsid);
+                (rsid, Index(index), outcome)
+            }
+            _ => return Err("Give either an rsid or an index"),
+        };
+        let body = serde_json::json!({
+            "rsid": rsid.map(|rsid| rsid.to_string()),
+            "index": index.0,
+            "value": value.to_string(),
+            "valid": outcome.is_valid(),
+            "curve": Bls12_381::NAME,
+            "max_degree": self.pp.max_degree(),
+        });
+        Ok((outcome, body))
+    }
+}
+
+/// 200 with the report of `verify --format json` if the proof is valid, 400
+/// with it if not, or 400 with an `error` if the request is malformed.
+#[cfg(feature = "server")]
+async fn serve_verify(
+    axum::extract::State(verifier): axum::extract::State<std::sync::Arc<Verifier>>,
+    body: axum::body::Bytes,
+) -> (axum::http::StatusCode, axum::Json<serde_json::Value>) {
+    use axum::http::StatusCode;
+
+    // parsed here rather than by the `Json` extractor, whose rejections are
+    // plain text and partly 422
+    let request = match serde_json::from_slice::<VerifyRequest>(&body) {
+        Ok(request) => request,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                axum::Json(serde_json::json!({ "error": "Malformed request" })),
+            )
+        }
+    };
+
+    // pairings are CPU-bound, keep them off the executor
+    let response = tokio::task::spawn_blocking(move || verifier.verify(&request)).await;
+    match response {
+        Ok(Ok((VerifyOutcome::Valid, body))) => (StatusCode::OK, axum::Json(body)),
+        Ok(Ok((VerifyOutcome::Invalid, body))) => (StatusCode::BAD_REQUEST, axum::Json(body)),
+        Ok(Err(error)) => (
+            StatusCode::BAD_REQUEST,
+            axum::Json(serde_json::json!({ "error": error })),
+        ),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(serde_json::json!({ "error": "Verification task failed" })),
+        ),
+    }
+}
+
+#[cfg(feature = "server")]
+fn serve(
+    pp_path: PathBuf,
+    rsid_path: PathBuf,
+    addr: std::net::SocketAddr,
+    encoding: Encoding,
+) -> Result<(), &'static str> {
+    let verifier = Verifier {
+        pp: open_pp(pp_path)?,
+        filter: open_rsid(&rsid_path)?,
+        encoding,
+    };
+    let app = axum::Router::new()
+        .route("/verify", axum::routing::post(serve_verify))
+        .with_state(std::sync::Arc::new(verifier));
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_io()
+        .build()
+        .map_err(|_| "Error starting the server runtime")?;
+    runtime.block_on(async {
+        let server = axum::Server::try_bind(&addr).map_err(|_| "Error binding the address")?;
+        println!("Listening on {}", addr);
+        server
+            .serve(app.into_make_service())
+            .await
+            .map_err(|_| "Server error")
+    })
+}
+
 fn main() -> Result<(), &'static str> {
-    env_logger::init();
+    // library warnings, e.g. rsids left out of a commitment, are for the user
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
 
     let cli = Cli::parse();
-    match cli {
-        Cli::Init { dest, degree } => setup(dest, degree),
-        Cli::Hash { vcf, pp, rsid } => hash(pp, vcf, rsid),
-        Cli::Prove {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(cli.jobs.unwrap_or(0))
+        .build()
+        .map_err(|_| "Error building the thread pool")?;
+
+    pool.install(|| match cli.command {
+        Command::Init {
+            dest,
+            degree,
+            shards,
+            gzip,
+            force,
+            seed,
+            curve,
+        } => with_curve!(curve, E => setup::<E>(dest, degree, shards, gzip, force, seed)),
+        Command::Hash {
+            pp,
             vcf,
+            blinding,
+            dry_run,
+            manifest,
+            report,
+            format,
+            curve,
+        } => with_curve!(curve, E => {
+            hash::<E>(pp, vcf, blinding, dry_run, manifest, report, format)
+        }),
+        Command::HashInline {
             pp,
-            index,
             rsid,
-        } => prove(pp, vcf, index, rsid),
-        Cli::Verify {
+            snps,
+            curve,
+        } => with_curve!(curve, E => hash_inline::<E>(pp, rsid, snps)),
+        Command::Prove {
+            vcf,
+            pp,
+            blinding,
+            id,
+            envelope,
+            manifest,
+            format,
+            curve,
+        } => with_curve!(curve, E => {
+            prove::<E>(pp, vcf, RsId(id), blinding, envelope, manifest, format)
+        }),
+        Command::ProveBatch {
+            vcf,
+            pp,
+            envelope,
+            ids,
+            curve,
+        } => with_curve!(curve, E => {
+            prove_batch::<E>(pp, vcf, ids.into_iter().map(RsId).collect(), envelope)
+        }),
+        Command::Verify {
             hash,
             proof,
             pp,
-            index,
+            id,
             rsid,
             value,
-        } => verify(pp, hash, proof, index, base_to_int(value.as_bytes()).into(), rsid),
-    }
+            encoding,
+            format,
+            curve,
+        } => with_curve!(curve, E => {
+            parse_value(&value, encoding)
+                .and_then(|value| verify::<E>(pp, hash, proof, RsId(id), value, rsid, format))
+        }),
+        Command::VerifyCommitment {
+            commitment,
+            proof,
+            pp,
+            id,
+            rsid,
+            value,
+            encoding,
+            format,
+            curve,
+        } => with_curve!(curve, E => {
+            parse_value(&value, encoding).and_then(|value| {
+                verify_commitment::<E>(pp, commitment, proof, RsId(id), value, rsid, format)
+            })
+        }),
+        Command::VerifyEnvelope {
+            pp,
+            rsid,
+            hash,
+            envelope,
+            curve,
+        } => with_curve!(curve, E => verify_envelope::<E>(pp, hash, envelope, rsid)),
+        Command::VerifyBatch {
+            pp,
+            rsid,
+            hash,
+            envelopes,
+            curve,
+        } => with_curve!(curve, E => verify_batch::<E>(pp, hash, envelopes, rsid)),
+        Command::Compare { a, b, curve } => with_curve!(curve, E => compare::<E>(a, b)),
+        Command::ValidatePp {
+            pp,
+            sample,
+            full,
+            curve,
+        } => with_curve!(curve, E => validate_pp::<E>(pp, sample, full)),
+        Command::Lookup { rsid, id } => lookup(rsid, RsId(id)),
+        #[cfg(feature = "server")]
+        Command::Serve {
+            pp,
+            rsid,
+            addr,
+            encoding,
+        } => serve(pp, rsid, addr, encoding),
+    })
+}
+
+#[test]
+fn test_read_envelopes() {
+    let dir = std::env::temp_dir().join(format!("dna-envelopes-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("envelopes");
+    std::fs::write(
+        &file,
+        "ab01\n\n\"cd02\"\n{\"rsid\": \"rs1\", \"proof\": \"00\", \"envelope\": \"ef03\"}\n",
+    )
+    .unwrap();
+    assert_eq!(read_envelopes(&file).unwrap(), ["ab01", "cd02", "ef03"]);
+
+    std::fs::write(&file, "\"ab01\n").unwrap();
+    assert!(read_envelopes(&file).is_err());
+    std::fs::write(&file, "{\"proof\": \"ef03\", \"envelope\": null}\n").unwrap();
+    assert!(read_envelopes(&file).is_err());
+
+    std::fs::write(&file, "\"cd02\"\n").unwrap();
+    std::fs::write(dir.join("a"), "ab01\n").unwrap();
+    assert_eq!(read_envelopes(&dir).unwrap(), ["ab01", "cd02"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "server")]
+#[test]
+fn test_serve_verify() {
+    use axum::http::StatusCode;
+    type F = <Bls12_381 as Pairing>::ScalarField;
+
+    let filter = HashMap::from([(RsId(1), Index(0)), (RsId(2), Index(1))]);
+    let (poly, _) = RsIdPolyBuilder::<F>::new(filter.clone())
+        .from_file("1\t100\trs1\tA\tG\n1\t200\trs2\tC\tT\n".as_bytes());
+    let pp = PublicParameters::<Bls12_381>::from_seed([0; 32], 2);
+    let hash = encode::<Bls12_381>(&RsIdHash::new(&pp, &poly).unwrap()).unwrap();
+    let proof = encode::<Bls12_381>(&RsIdHash::prove(&pp, &poly, Index(1)).unwrap()).unwrap();
+    let verifier = std::sync::Arc::new(Verifier {
+        pp,
+        filter,
+        encoding: Encoding::Base,
+    });
+
+    let request = |rsid: Option<&str>, index: Option<usize>, value: &str| VerifyRequest {
+        rsid: rsid.map(String::from),
+        index,
+        hash: hash.clone(),
+        proof: proof.clone(),
+        value: value.to_string(),
+    };
+    let (outcome, body) = verifier.verify(&request(Some("rs2"), None, "T")).unwrap();
+    assert_eq!(outcome, VerifyOutcome::Valid);
+    assert_eq!(body["index"], 1);
+    let (outcome, body) = verifier.verify(&request(None, Some(1), "T")).unwrap();
+    assert_eq!(outcome, VerifyOutcome::Valid);
+    assert_eq!(body["rsid"], "rs2");
+    let (outcome, _) = verifier.verify(&request(None, Some(1), "C")).unwrap();
+    assert_eq!(outcome, VerifyOutcome::Invalid);
+    assert!(verifier.verify(&request(None, None, "T")).is_err());
+    assert!(verifier
+        .verify(&request(Some("rs2"), Some(1), "T"))
+        .is_err());
+    assert!(verifier.verify(&request(Some("rs3"), None, "T")).is_err());
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let status = |body: String| {
+        let state = axum::extract::State(verifier.clone());
+        runtime.block_on(serve_verify(state, body.into())).0
+    };
+    let body = |index: usize, value: &str| {
+        serde_json::json!({ "index": index, "hash": hash, "proof": proof, "value": value })
+            .to_string()
+    };
+    assert_eq!(status(body(1, "T")), StatusCode::OK);
+    assert_eq!(status(body(1, "C")), StatusCode::BAD_REQUEST);
+    assert_eq!(status(body(0, "T")), StatusCode::BAD_REQUEST);
+    assert_eq!(status("{\"index\": 1".to_string()), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        status(serde_json::json!({ "index": 1, "hash": hash }).to_string()),
+        StatusCode::BAD_REQUEST
+    );
 }
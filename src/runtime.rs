@@ -0,0 +1,69 @@
+//! Async wrappers for servers: hashing and proving are CPU-bound, so they run
+//! on tokio's blocking pool (where rayon still parallelizes the MSMs) instead
+//! of stalling the executor. Only built with the `tokio` feature.
+use std::future::Future;
+use std::sync::Arc;
+
+use ark_ec::pairing::Pairing;
+use log::error;
+
+use crate::commitment::{PointProof, PublicParameters};
+use crate::dna::{Index, RsIdHash, RsIdPoly};
+use crate::error::Error;
+
+async fn spawn<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, Error> + Send + 'static,
+) -> Result<T, Error> {
+    tokio::task::spawn_blocking(f).await.map_err(|e| {
+        error!("Blocking task failed: {}", e);
+        Error::Io(e.into())
+    })?
+}
+
+/// Async [`RsIdHash::new`].
+pub fn spawn_commit<E: Pairing>(
+    pp: Arc<PublicParameters<E>>,
+    poly: Arc<RsIdPoly<E::ScalarField>>,
+) -> impl Future<Output = Result<RsIdHash<E>, Error>> {
+    spawn(move || RsIdHash::new(&pp, &poly))
+}
+
+/// Async [`RsIdHash::prove`].
+pub fn spawn_prove<E: Pairing>(
+    pp: Arc<PublicParameters<E>>,
+    poly: Arc<RsIdPoly<E::ScalarField>>,
+    index: Index,
+) -> impl Future<Output = Result<PointProof<E>, Error>> {
+    spawn(move || RsIdHash::prove(&pp, &poly, index))
+}
+
+#[test]
+fn test_spawn_prove() {
+    use crate::commitment::VerifyOutcome;
+    use crate::dna::{RsId, RsIdPolyBuilder};
+    use std::collections::HashMap;
+
+    type E = ark_bls12_381::Bls12_381;
+    type F = <E as Pairing>::ScalarField;
+
+    let vcf = "1\t100\trs1\tA\tG\n1\t200\trs2\tC\tT\n";
+    let filter = HashMap::from([(RsId(1), Index(0)), (RsId(2), Index(1))]);
+    let (poly, _) = RsIdPolyBuilder::<F>::new(filter).from_file(vcf.as_bytes());
+    let poly = Arc::new(poly);
+    let pp = Arc::new(PublicParameters::<E>::new(&mut rand::thread_rng(), 2));
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let (hash, proof) = runtime.block_on(async {
+        let hash = spawn_commit(pp.clone(), poly.clone()).await.unwrap();
+        let proof = spawn_prove(pp.clone(), poly.clone(), Index(1))
+            .await
+            .unwrap();
+        (hash, proof)
+    });
+    assert_eq!(
+        proof.verify(&pp, &hash.commitment(), 1, F::from(1u8)),
+        Ok(VerifyOutcome::Valid)
+    );
+}
@@ -0,0 +1,188 @@
+//! Public parameters split across several files, which are easier to serve
+//! from a CDN and to resume than a single large one. A JSON manifest lists the
+//! shards in order together with their SHA-256, so that a corrupt or truncated
+//! shard is caught on load.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use log::error;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::{Component, Path};
+
+use crate::commitment::PublicParameters;
+use crate::error::Error;
+use crate::header::CurveId;
+
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub curve: u8,
+    /// Compressed powers of τ in G2, hex-encoded: there are too few to shard.
+    pub powers_of_g2: String,
+    pub shards: Vec<Shard>,
+}
+
+/// A contiguous run of the powers of τ in G1.
+#[derive(Serialize, Deserialize)]
+pub struct Shard {
+    /// Path of the shard, relative to the manifest.
+    pub file: String,
+    /// Hex-encoded SHA-256 of the shard file.
+    pub sha256: String,
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Returns whether `reader` holds a manifest rather than serialized parameters,
+/// without consuming anything.
+pub fn is_manifest(reader: &mut impl BufRead) -> io::Result<bool> {
+    Ok(reader.fill_buf()?.first() == Some(&b'{'))
+}
+
+/// Writes `pp` as a manifest at `manifest_path` and `shards` files next to it,
+/// named after the manifest with the shard number appended.
+pub fn write_sharded<E: CurveId>(
+    pp: &PublicParameters<E>,
+    manifest_path: &Path,
+    shards: usize,
+) -> Result<(), Error> {
+    let name = manifest_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| invalid_data("Invalid manifest path"))?;
+    let dir = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut powers_of_g2 = Vec::new();
+    pp.powers_of_g2
+        .serialize_compressed(&mut powers_of_g2)
+        .map_err(Error::Serialization)?;
+    let mut manifest = Manifest {
+        curve: E::CURVE_ID,
+        powers_of_g2: hex::encode(powers_of_g2),
+        shards: Vec::with_capacity(shards),
+    };
+
+    let shard_size = pp.powers_of_g.len().div_ceil(shards.max(1)).max(1);
+    for (i, powers) in pp.powers_of_g.chunks(shard_size).enumerate() {
+        let mut bytes = Vec::new();
+        powers
+            .serialize_compressed(&mut bytes)
+            .map_err(Error::Serialization)?;
+        let file = format!("{}.{}", name, i);
+        std::fs::write(dir.join(&file), &bytes)?;
+        manifest.shards.push(Shard {
+            file,
+            sha256: hex::encode(Sha256::digest(&bytes)),
+        });
+    }
+
+    let mut writer = BufWriter::new(File::create(manifest_path)?);
+    serde_json::to_writer_pretty(&mut writer, &manifest).map_err(io::Error::from)?;
+    Ok(writer.flush()?)
+}
+
+/// Reassembles the parameters described by the manifest at `manifest_path`.
+/// Shards are memory-mapped, so only the pages being decoded are resident, and
+/// each is checked against its digest before being decoded.
+pub fn read_sharded<E: CurveId>(manifest_path: &Path) -> Result<PublicParameters<E>, Error> {
+    let manifest: Manifest =
+        serde_json::from_reader(io::BufReader::new(File::open(manifest_path)?))
+            .map_err(io::Error::from)?;
+    if manifest.curve != E::CURVE_ID {
+        return Err(invalid_data("The manifest was generated for another curve").into());
+    }
+    let dir = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+
+    let powers_of_g2 = hex::decode(&manifest.powers_of_g2)
+        .map_err(|_| invalid_data("Invalid G2 powers in the manifest"))?;
+    let powers_of_g2 = Vec::<E::G2Affine>::deserialize_compressed_unchecked(&*powers_of_g2)
+        .inspect_err(|_| error!("Invalid G2 powers in the manifest"))?;
+
+    let mut powers_of_g = Vec::new();
+    for shard in &manifest.shards {
+        if !is_plain_file_name(&shard.file) {
+            error!(
+                "Shard {} is not a file name next to the manifest",
+                shard.file
+            );
+            return Err(invalid_data("Invalid shard file name").into());
+        }
+        let file = File::open(dir.join(&shard.file))?;
+        // SAFETY: shards are only read, and are not expected to change under us.
+        #[cfg(not(target_arch = "wasm32"))]
+        let bytes = unsafe { memmap::Mmap::map(&file)? };
+        #[cfg(target_arch = "wasm32")]
+        let bytes = {
+            let mut bytes = Vec::new();
+            io::Read::read_to_end(&mut &file, &mut bytes)?;
+            bytes
+        };
+        if hex::encode(Sha256::digest(&bytes[..])) != shard.sha256 {
+            error!("Checksum mismatch in shard {}", shard.file);
+            return Err(invalid_data("Corrupt shard").into());
+        }
+        let powers = Vec::<E::G1Affine>::deserialize_compressed_unchecked(&bytes[..])
+            .inspect_err(|_| error!("Error deserializing shard {}", shard.file))?;
+        powers_of_g.extend(powers);
+    }
+
+    Ok(PublicParameters {
+        powers_of_g,
+        powers_of_g2,
+    })
+}
+
+/// Whether `file` names a file in the manifest's directory, rather than a path
+/// that could lead outside it (`../pp.bin`, `/etc/passwd`, `a/b`).
+fn is_plain_file_name(file: &str) -> bool {
+    let mut components = Path::new(file).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) && !file.contains(['/', '\\'])
+}
+
+#[test]
+fn test_sharded() {
+    type E = ark_bls12_381::Bls12_381;
+
+    let dir = std::env::temp_dir().join(format!("dna-shard-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let manifest = dir.join("pp.json");
+
+    let pp = PublicParameters::<E>::new(&mut rand::thread_rng(), 5);
+    write_sharded(&pp, &manifest, 3).unwrap();
+    assert!(is_manifest(&mut io::BufReader::new(File::open(&manifest).unwrap())).unwrap());
+    assert_eq!(read_sharded::<E>(&manifest).unwrap().digest(), pp.digest());
+
+    let shard = dir.join("pp.json.1");
+    let mut bytes = std::fs::read(&shard).unwrap();
+    *bytes.last_mut().unwrap() ^= 1;
+    std::fs::write(&shard, bytes).unwrap();
+    assert!(read_sharded::<E>(&manifest).is_err());
+
+    for file in [
+        "../pp.json.0",
+        "/etc/passwd",
+        "sub/pp.json.0",
+        "..",
+        ".",
+        "",
+    ] {
+        assert!(!is_plain_file_name(file), "{}", file);
+    }
+    let contents = std::fs::read_to_string(&manifest).unwrap();
+    std::fs::write(
+        &manifest,
+        contents.replace("\"pp.json.0\"", "\"../pp.json.0\""),
+    )
+    .unwrap();
+    assert!(matches!(
+        read_sharded::<E>(&manifest),
+        Err(Error::Io(e)) if e.kind() == io::ErrorKind::InvalidData
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,148 @@
+//! Bindings for running the CLI's `hash`, `prove` and `verify` in a browser or
+//! in Node, so that a genome never leaves the user's machine. Inputs and outputs
+//! are those of the CLI: public parameters as the bytes of a `pp.bin` (gzipped
+//! or not), hashes and proofs as the same hex strings, over BLS12-381.
+//!
+//! Build with `wasm-pack build wasm --target nodejs`, which links these into a
+//! cdylib through the `wasm` crate; without threads rayon runs everything on the
+//! calling thread. `wasm/test.js` checks the bindings in Node.
+use std::collections::HashMap;
+use std::io::{BufRead, Read};
+
+use ark_bls12_381::Bls12_381;
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use flate2::read::MultiGzDecoder;
+use wasm_bindgen::prelude::*;
+
+use crate::commitment::{PointProof, PublicParameters, VerifyOutcome};
+use crate::dna::{
+    parse_value, read_rsid_list, value_encoding, Index, RsId, RsIdHash, RsIdPolyBuilder,
+};
+use crate::envelope::ALLELE_HASH_ENCODING;
+use crate::header::{read_curve_id, write_curve_id, CurveId};
+
+type E = Bls12_381;
+type F = <E as Pairing>::ScalarField;
+
+/// Parameters derived from a 32-byte `seed`, as written by `init --seed`.
+/// Anyone with the seed can forge proofs: for tests and demos only.
+#[wasm_bindgen]
+pub fn setup(seed: &[u8], log_degree: usize) -> Result<Vec<u8>, JsError> {
+    let seed = seed
+        .try_into()
+        .map_err(|_| JsError::new("The seed must be 32 bytes"))?;
+    let mut bytes = Vec::new();
+    write_curve_id::<E>(&mut bytes)?;
+    PublicParameters::<E>::from_seed(seed, log_degree).serialize_compressed(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// The hex hash of the variants of `vcf` listed in `rsid_list`, with alleles
+/// encoded as by the CLI's `--encoding`, e.g. `BASE_ENCODING`, 0.
+#[wasm_bindgen]
+pub fn hash_vcf(pp: &[u8], vcf: &str, rsid_list: &str, encoding: u8) -> Result<String, JsError> {
+    let pp = read_pp(pp)?;
+    let filter = read_rsid_list(rsid_list.as_bytes())?;
+    let (poly, stats) = vcf_builder(filter, encoding)?.from_file(vcf.as_bytes());
+    if stats.matched == 0 {
+        return Err(JsError::new("No rsid from the list was found in the vcf"));
+    }
+    encode(&RsIdHash::<E>::new(&pp, &poly)?)
+}
+
+/// The hex proof of the value of `rsid`, e.g. `rs123`, in `vcf`.
+#[wasm_bindgen]
+pub fn prove(
+    pp: &[u8],
+    vcf: &str,
+    rsid_list: &str,
+    rsid: &str,
+    encoding: u8,
+) -> Result<String, JsError> {
+    let pp = read_pp(pp)?;
+    let filter = read_rsid_list(rsid_list.as_bytes())?;
+    let index = *filter
+        .get(&parse_rsid(rsid)?)
+        .ok_or_else(|| JsError::new("rsid not in the list"))?;
+    let (poly, _) = vcf_builder(filter, encoding)?.from_file(vcf.as_bytes());
+    let proof = RsIdHash::<E>::prove(&pp, &poly, index)
+        .map_err(|_| JsError::new("The rsid list is too long for the parameters"))?;
+    encode(&proof)
+}
+
+/// Whether `proof` opens `hash` to `value` at `rsid`; the value is an allele or
+/// a decimal, as given to the CLI.
+#[wasm_bindgen]
+pub fn verify(
+    pp: &[u8],
+    hash: &str,
+    proof: &str,
+    rsid_list: &str,
+    rsid: &str,
+    value: &str,
+    encoding: u8,
+) -> Result<bool, JsError> {
+    let pp = read_pp(pp)?;
+    let filter = read_rsid_list(rsid_list.as_bytes())?;
+    let index = *filter
+        .get(&parse_rsid(rsid)?)
+        .ok_or_else(|| JsError::new("rsid not in the list"))?;
+    let hash: RsIdHash<E> = decode(hash)?;
+    let proof: PointProof<E> = decode(proof)?;
+    let value = parse_value(value, encoding)
+        .map_err(|_| JsError::new("The value must be below the field modulus"))?;
+    let outcome = proof
+        .verify(&pp, &hash.commitment(), index.0, value)
+        .map_err(|_| JsError::new("Index outside the public parameters"))?;
+    Ok(outcome == VerifyOutcome::Valid)
+}
+
+fn vcf_builder(filter: HashMap<RsId, Index>, encoding: u8) -> Result<RsIdPolyBuilder<F>, JsError> {
+    if encoding == ALLELE_HASH_ENCODING {
+        return Ok(RsIdPolyBuilder::new(filter).allele_hash());
+    }
+    let encoding =
+        value_encoding(encoding).ok_or_else(|| JsError::new("Unknown value encoding"))?;
+    Ok(RsIdPolyBuilder::with_encoding(filter, encoding))
+}
+
+fn parse_rsid(rsid: &str) -> Result<RsId, JsError> {
+    rsid.parse().map_err(|_| JsError::new("Invalid rsid"))
+}
+
+fn read_pp(bytes: &[u8]) -> Result<PublicParameters<E>, JsError> {
+    let mut decompressed = Vec::new();
+    let mut bytes = if bytes.starts_with(&[0x1f, 0x8b]) {
+        MultiGzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+        &decompressed[..]
+    } else {
+        bytes
+    };
+    check_curve(&mut bytes)?;
+    Ok(PublicParameters::deserialize_compressed_unchecked(bytes)?)
+}
+
+/// Hex-encodes `value` behind a header naming the curve, as the CLI does.
+fn encode(value: &impl CanonicalSerialize) -> Result<String, JsError> {
+    let mut bytes = Vec::new();
+    write_curve_id::<E>(&mut bytes)?;
+    value.serialize_compressed(&mut bytes)?;
+    Ok(hex::encode(bytes))
+}
+
+fn decode<T: CanonicalDeserialize>(input: &str) -> Result<T, JsError> {
+    let bytes = hex::decode(input.trim())?;
+    let mut bytes = &bytes[..];
+    check_curve(&mut bytes)?;
+    Ok(T::deserialize_compressed(bytes)?)
+}
+
+fn check_curve(reader: &mut impl BufRead) -> Result<(), JsError> {
+    if read_curve_id(reader)? != E::CURVE_ID {
+        return Err(JsError::new(
+            "curve mismatch: the input was produced for another curve",
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,2 @@
+//! The `dna_proofs` wasm bindings as a cdylib for `wasm-pack`.
+pub use dna_proofs::wasm::*;
@@ -0,0 +1,48 @@
+// Checks the wasm bindings in Node against the example VCF. Run from the
+// repository root:
+//
+//   wasm-pack build wasm --target nodejs
+//   node wasm/test.js
+const assert = require("node:assert/strict");
+const { readFileSync } = require("node:fs");
+const path = require("node:path");
+const dna = require("./pkg/dna_proofs_wasm.js");
+
+const root = path.join(__dirname, "..");
+const vcf = readFileSync(path.join(root, "example.vcf"), "utf8");
+const rsidList = readFileSync(path.join(root, "rsidlist"), "utf8");
+
+const pp = dna.setup(new Uint8Array(32).fill(7), 6);
+// encoding ids, as recorded in envelopes
+const BASE = 0;
+const DISTINCT = 1;
+
+const hash = dna.hash_vcf(pp, vcf, rsidList, BASE);
+// rs34877994 is an A>G variant in the example VCF
+const proof = dna.prove(pp, vcf, rsidList, "rs34877994", BASE);
+
+assert.equal(dna.verify(pp, hash, proof, rsidList, "rs34877994", "G", BASE), true);
+assert.equal(dna.verify(pp, hash, proof, rsidList, "rs34877994", "A", BASE), false);
+assert.equal(dna.verify(pp, hash, proof, rsidList, "rs8652", "G", BASE), false);
+assert.throws(() => dna.prove(pp, vcf, rsidList, "rs1", BASE), /not in the list/);
+assert.throws(() => dna.hash_vcf(pp, vcf, rsidList, 42), /Unknown value encoding/);
+
+// the base encoding does not tell G from C, the distinct one does
+const distinctHash = dna.hash_vcf(pp, vcf, rsidList, DISTINCT);
+const distinctProof = dna.prove(pp, vcf, rsidList, "rs34877994", DISTINCT);
+assert.equal(dna.verify(pp, hash, proof, rsidList, "rs34877994", "C", BASE), true);
+assert.equal(
+  dna.verify(pp, distinctHash, distinctProof, rsidList, "rs34877994", "G", DISTINCT),
+  true,
+);
+assert.equal(
+  dna.verify(pp, distinctHash, distinctProof, rsidList, "rs34877994", "C", DISTINCT),
+  false,
+);
+
+// the same seed gives the same parameters, hence the same hash
+assert.equal(
+  dna.hash_vcf(dna.setup(new Uint8Array(32).fill(7), 6), vcf, rsidList, BASE),
+  hash,
+);
+console.log("ok");